@@ -0,0 +1,61 @@
+use std::alloc::{Allocator, Global};
+
+use indexmap::IndexSet;
+
+use crate::StrRef;
+
+// Copies every interned string into a box allocated from `A`, e.g. an arena
+// living in a pre-allocated region. `String` takes no allocator, so this is
+// a pool of its own rather than a parameter of `StrPool`: it has no static
+// entries and none of the policies, only deduplication by content.
+pub struct AllocPool<A: Allocator + Clone = Global> {
+    pool: IndexSet<Box<str, A>>,
+    alloc: A,
+}
+
+impl AllocPool {
+    pub fn new() -> AllocPool {
+        AllocPool::new_in(Global)
+    }
+}
+
+impl Default for AllocPool {
+    fn default() -> Self {
+        AllocPool::new()
+    }
+}
+
+impl<A: Allocator + Clone> AllocPool<A> {
+    pub fn new_in(alloc: A) -> AllocPool<A> {
+        AllocPool { pool: IndexSet::new(), alloc }
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    // Allocates only on a miss, one box per new string.
+    pub fn intern(&mut self, str: &str) -> StrRef {
+        if let Some(ptr) = self.pool.get_index_of(str) {
+            return StrRef::new(ptr);
+        }
+        let mut bytes = Vec::with_capacity_in(str.len(), self.alloc.clone());
+        bytes.extend_from_slice(str.as_bytes());
+        let (raw, alloc) = Box::into_raw_with_allocator(bytes.into_boxed_slice());
+        let boxed = unsafe { Box::from_raw_in(raw as *mut str, alloc) };
+        let (ptr, _) = self.pool.insert_full(boxed);
+        StrRef::new(ptr)
+    }
+
+    pub fn get(&self, r: StrRef) -> Option<&str> {
+        self.pool.get_index(r.ptr).map(|s| &**s)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pool.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pool.is_empty()
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    cell::UnsafeCell,
+    collections::HashMap,
+    ptr, slice, str,
+    sync::{
+        atomic::{AtomicPtr, AtomicUsize, Ordering},
+        Mutex, PoisonError,
+    },
+};
+
+use crate::StrRef;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+// Segment `i` holds `FIRST_SEGMENT << i` slots, so 32 of them never run out.
+const FIRST_SEGMENT: usize = 64;
+const SEGMENTS: usize = 32;
+
+type Slot = UnsafeCell<(*const u8, usize)>;
+
+// Shared between threads, typically as a `static`. Interning takes a lock,
+// resolving does not: string bytes live in chunks that are never moved or
+// freed before the pool, and slots are written once, before `len` publishes
+// them. So a `static` pool hands out `&'static str`.
+pub struct SyncArenaPool {
+    segments: [AtomicPtr<Slot>; SEGMENTS],
+    len: AtomicUsize,
+    writer: Mutex<Writer>,
+}
+
+unsafe impl Send for SyncArenaPool {}
+unsafe impl Sync for SyncArenaPool {}
+
+struct Writer {
+    // Keys borrow from `chunks`.
+    keys: Option<HashMap<&'static str, usize>>,
+    chunks: Vec<*mut [u8]>,
+    current: *mut u8,
+    used: usize,
+}
+
+fn locate(ptr: usize) -> (usize, usize) {
+    let n = ptr / FIRST_SEGMENT + 1;
+    let segment = (usize::BITS - 1 - n.leading_zeros()) as usize;
+    (segment, ptr - FIRST_SEGMENT * ((1 << segment) - 1))
+}
+
+impl Writer {
+    fn new_chunk(&mut self, cap: usize) -> *mut u8 {
+        let chunk = Box::into_raw(vec![0u8; cap].into_boxed_slice());
+        self.chunks.push(chunk);
+        chunk as *mut u8
+    }
+
+    fn alloc(&mut self, str: &str) -> &'static str {
+        let len = str.len();
+        let dst = if len > CHUNK_SIZE {
+            self.new_chunk(len)
+        } else {
+            if self.current.is_null() || self.used + len > CHUNK_SIZE {
+                self.current = self.new_chunk(CHUNK_SIZE);
+                self.used = 0;
+            }
+            let dst = unsafe { self.current.add(self.used) };
+            self.used += len;
+            dst
+        };
+        unsafe {
+            ptr::copy_nonoverlapping(str.as_ptr(), dst, len);
+            str::from_utf8_unchecked(slice::from_raw_parts(dst, len))
+        }
+    }
+}
+
+impl SyncArenaPool {
+    pub const fn new() -> SyncArenaPool {
+        SyncArenaPool {
+            segments: [const { AtomicPtr::new(ptr::null_mut()) }; SEGMENTS],
+            len: AtomicUsize::new(0),
+            writer: Mutex::new(Writer {
+                keys: None,
+                chunks: Vec::new(),
+                current: ptr::null_mut(),
+                used: 0,
+            }),
+        }
+    }
+
+    pub fn intern(&self, str: &str) -> StrRef {
+        let mut writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(&ptr) = writer.keys.get_or_insert_with(HashMap::new).get(str) {
+            return StrRef::new(ptr);
+        }
+        let ptr = self.len.load(Ordering::Relaxed);
+        let (segment, offset) = locate(ptr);
+        let mut slots = self.segments[segment].load(Ordering::Relaxed);
+        if slots.is_null() {
+            let new: Box<[Slot]> = (0..FIRST_SEGMENT << segment)
+                .map(|_| UnsafeCell::new((ptr::null(), 0)))
+                .collect();
+            slots = Box::into_raw(new) as *mut Slot;
+            self.segments[segment].store(slots, Ordering::Release);
+        }
+        let stored = writer.alloc(str);
+        unsafe { *(*slots.add(offset)).get() = (stored.as_ptr(), stored.len()) };
+        self.len.store(ptr + 1, Ordering::Release);
+        writer.keys.as_mut().unwrap().insert(stored, ptr);
+        StrRef::new(ptr)
+    }
+
+    pub fn resolve(&self, r: StrRef) -> Option<&str> {
+        if r.ptr >= self.len.load(Ordering::Acquire) {
+            return None;
+        }
+        let (segment, offset) = locate(r.ptr);
+        let slots = self.segments[segment].load(Ordering::Acquire);
+        let (ptr, len) = unsafe { *(*slots.add(offset)).get() };
+        Some(unsafe { str::from_utf8_unchecked(slice::from_raw_parts(ptr, len)) })
+    }
+
+    // Panics unless every entry is stored once, maps back to its own index
+    // and lies in a chunk after the entry before it in the same chunk. Like
+    // `debug_assert!`, checks nothing in release builds.
+    pub fn debug_check_invariants(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        let writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let len = self.len();
+        let keys = writer.keys.as_ref().map_or(0, HashMap::len);
+        assert_eq!(keys, len, "dedup map and slots disagree");
+        let mut prev: Option<(usize, usize)> = None;
+        for ptr in 0..len {
+            let str = self.resolve(StrRef::new(ptr)).unwrap();
+            let owner = writer.keys.as_ref().and_then(|keys| keys.get(str));
+            assert_eq!(owner, Some(&ptr), "entry {} is duplicated or missing", ptr);
+            let start = str.as_ptr() as usize;
+            let chunk = writer.chunks.iter().position(|&chunk| {
+                let base = chunk as *mut u8 as usize;
+                base <= start && start + str.len() <= base + chunk.len()
+            });
+            let chunk = chunk.expect("entry outside of the arena");
+            if let Some((prev_chunk, prev_end)) = prev {
+                assert!(prev_chunk != chunk || prev_end <= start, "arena offsets out of order");
+            }
+            prev = Some((chunk, start + str.len()));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Acquire)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for SyncArenaPool {
+    fn default() -> Self {
+        SyncArenaPool::new()
+    }
+}
+
+impl Drop for SyncArenaPool {
+    fn drop(&mut self) {
+        for (segment, slots) in self.segments.iter_mut().enumerate() {
+            let slots = *slots.get_mut();
+            if !slots.is_null() {
+                let len = FIRST_SEGMENT << segment;
+                drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(slots, len)) });
+            }
+        }
+        let writer = self.writer.get_mut().unwrap_or_else(PoisonError::into_inner);
+        writer.keys = None;
+        for &chunk in &writer.chunks {
+            drop(unsafe { Box::from_raw(chunk) });
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::{hint::black_box, time::Instant};
+
+use crate::{StrPool, StrRef};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchStats {
+    pub intern_ns_per_op: f64,
+    pub resolve_ns_per_op: f64,
+    // Fraction of interns that hit an existing entry.
+    pub dedup_ratio: f64,
+}
+
+// Interns the workload into a fresh pool, then resolves every ref once. Timings
+// are wall clock, so runs of the same workload are only comparable on the same
+// machine.
+pub fn run<S: AsRef<str>>(workload: &[S]) -> BenchStats {
+    let ops = workload.len().max(1) as f64;
+    let mut pool = StrPool::default();
+
+    let start = Instant::now();
+    let refs: Vec<StrRef> = workload.iter().map(|s| pool.get_or_intern(s.as_ref())).collect();
+    let intern_ns = start.elapsed().as_nanos() as f64;
+
+    let start = Instant::now();
+    for r in &refs {
+        black_box(pool.get(r.clone()));
+    }
+    let resolve_ns = start.elapsed().as_nanos() as f64;
+
+    BenchStats {
+        intern_ns_per_op: intern_ns / ops,
+        resolve_ns_per_op: resolve_ns / ops,
+        dedup_ratio: (workload.len() - pool.len()) as f64 / ops,
+    }
+}
@@ -0,0 +1,32 @@
+// Guarded batch resolution against a `deref` per ref. Run with
+// `cargo bench --bench read_guard`.
+use std::{hint::black_box, time::Instant};
+
+use strpool::StrRef;
+
+const ROUNDS: usize = 200;
+
+fn main() {
+    let refs: Vec<StrRef> = (0..1000).map(|i| strpool::put_heap(format!("token{}", i))).collect();
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for r in &refs {
+            black_box(&**r);
+        }
+    }
+    let deref = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        let guard = strpool::read_guard();
+        for r in &refs {
+            black_box(guard.resolve(r.clone()));
+        }
+    }
+    let guarded = start.elapsed();
+
+    let ops = (ROUNDS * refs.len()) as f64;
+    println!("deref       {:>8.2} ns/op", deref.as_nanos() as f64 / ops);
+    println!("read_guard  {:>8.2} ns/op", guarded.as_nanos() as f64 / ops);
+}
@@ -0,0 +1,21 @@
+// Derefs of a small rotating set of refs, which stay in the resolve cache,
+// against a set too large for it. Run with `cargo bench --bench resolve_cache`.
+use std::{hint::black_box, time::Instant};
+
+use strpool::StrRef;
+
+const OPS: usize = 1 << 20;
+
+fn derefs(refs: &[StrRef]) -> f64 {
+    let start = Instant::now();
+    for i in 0..OPS {
+        black_box(&*refs[i % refs.len()]);
+    }
+    start.elapsed().as_nanos() as f64 / OPS as f64
+}
+
+fn main() {
+    let refs: Vec<StrRef> = (0..1024).map(|i| strpool::put_heap(format!("cell{}", i))).collect();
+    println!("8 refs     {:>8.2} ns/op", derefs(&refs[..8]));
+    println!("1024 refs  {:>8.2} ns/op", derefs(&refs));
+}
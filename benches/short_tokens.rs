@@ -0,0 +1,29 @@
+// Lookups of tokens of up to 8 bytes, which take the packed `u64` path, against
+// tokens just past it that go through the main set. Run with
+// `cargo bench --bench short_tokens`.
+use std::{hint::black_box, time::Instant};
+
+use strpool::StrPool;
+
+const ROUNDS: usize = 200;
+
+fn lookups(tokens: &[String]) -> f64 {
+    let mut pool = StrPool::default();
+    for token in tokens {
+        pool.put_heap(token.clone());
+    }
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for token in tokens {
+            black_box(pool.get_or_intern(black_box(token)));
+        }
+    }
+    start.elapsed().as_nanos() as f64 / (ROUNDS * tokens.len()) as f64
+}
+
+fn main() {
+    let short: Vec<String> = (0..1000).map(|i| format!("t{:07}", i)).collect();
+    let long: Vec<String> = (0..1000).map(|i| format!("t{:08}", i)).collect();
+    println!("8 bytes  {:>8.2} ns/op", lookups(&short));
+    println!("9 bytes  {:>8.2} ns/op", lookups(&long));
+}
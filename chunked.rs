@@ -0,0 +1,106 @@
+use std::{ops::Range, ptr, slice, str};
+use indexmap::IndexSet;
+
+use crate::StrRef;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// Interned bytes are copied into fixed-size chunks which are never grown,
+// moved or freed before the pool itself, so resolved slices stay valid for the
+// lifetime of the pool. Strings longer than a chunk get a chunk of their own.
+#[derive(Default)]
+pub struct ChunkedPool {
+    chunks: Vec<(*mut u8, usize)>,
+    current: Option<usize>,
+    used: usize,
+    // Keys borrow from `chunks` and are only handed out tied to `&self`.
+    keys: IndexSet<&'static str>,
+    entries: Vec<(usize, usize, usize)>,
+}
+
+unsafe impl Send for ChunkedPool {}
+unsafe impl Sync for ChunkedPool {}
+
+impl ChunkedPool {
+    fn new_chunk(&mut self, cap: usize) -> usize {
+        let chunk = Box::into_raw(vec![0u8; cap].into_boxed_slice());
+        self.chunks.push((chunk as *mut u8, cap));
+        self.chunks.len() - 1
+    }
+
+    fn alloc(&mut self, len: usize) -> (usize, usize) {
+        if len > CHUNK_SIZE {
+            return (self.new_chunk(len), 0);
+        }
+        match self.current {
+            Some(chunk) if self.used + len <= CHUNK_SIZE => {
+                let offset = self.used;
+                self.used += len;
+                (chunk, offset)
+            }
+            _ => {
+                let chunk = self.new_chunk(CHUNK_SIZE);
+                self.current = Some(chunk);
+                self.used = len;
+                (chunk, 0)
+            }
+        }
+    }
+
+    unsafe fn slice(&self, (chunk, offset, len): (usize, usize, usize)) -> &'static str {
+        let (base, _) = self.chunks[chunk];
+        str::from_utf8_unchecked(slice::from_raw_parts(base.add(offset), len))
+    }
+
+    pub fn put(&mut self, str: &str) -> StrRef {
+        if let Some(ptr) = self.keys.get_index_of(str) {
+            return StrRef::new(ptr);
+        }
+        let (chunk, offset) = self.alloc(str.len());
+        let entry = (chunk, offset, str.len());
+        let key = unsafe {
+            let (base, _) = self.chunks[chunk];
+            ptr::copy_nonoverlapping(str.as_ptr(), base.add(offset), str.len());
+            self.slice(entry)
+        };
+        let (ptr, _) = self.keys.insert_full(key);
+        self.entries.push(entry);
+        StrRef::new(ptr)
+    }
+
+    // Stores no bytes of its own, the entry points into those of `parent`.
+    // Panics if `parent` is not in this pool or `range` is out of bounds or
+    // does not fall on char boundaries, like slicing a `str` would.
+    pub fn intern_substr_of(&mut self, parent: StrRef, range: Range<usize>) -> StrRef {
+        let (chunk, offset, _) = self.entries[parent.ptr];
+        let str = unsafe { self.slice(self.entries[parent.ptr]) };
+        let sub = &str[range.clone()];
+        if let Some(ptr) = self.keys.get_index_of(sub) {
+            return StrRef::new(ptr);
+        }
+        let (ptr, _) = self.keys.insert_full(sub);
+        self.entries.push((chunk, offset + range.start, sub.len()));
+        StrRef::new(ptr)
+    }
+
+    pub fn get(&self, r: StrRef) -> Option<&str> {
+        self.entries.get(r.ptr).map(|&entry| unsafe { self.slice(entry) })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Drop for ChunkedPool {
+    fn drop(&mut self) {
+        self.keys.clear();
+        for &(base, cap) in &self.chunks {
+            drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(base, cap)) });
+        }
+    }
+}
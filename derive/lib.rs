@@ -0,0 +1,88 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, GenericParam, Ident,
+    Type,
+};
+
+enum Kind {
+    Owned,
+    Borrowed,
+    Other,
+}
+
+fn kind(ty: &Type) -> Kind {
+    match ty {
+        Type::Path(ty) if ty.qself.is_none() && ty.path.is_ident("String") => Kind::Owned,
+        Type::Reference(ty) if ty.mutability.is_none() => match &*ty.elem {
+            Type::Path(elem) if elem.qself.is_none() && elem.path.is_ident("str") => Kind::Borrowed,
+            _ => Kind::Other,
+        },
+        _ => Kind::Other,
+    }
+}
+
+// `#[derive(Intern)]` on `Foo` generates `FooInterned`, with every `String` and
+// `&str` field turned into a `StrRef`, and `Foo::intern(self) -> FooInterned`,
+// which interns those fields into the global pool. Other fields are moved
+// over as they are. The interned struct drops the lifetime parameters, so
+// only string fields may borrow.
+#[proc_macro_derive(Intern)]
+pub fn derive_intern(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(input.span(), "Intern can only be derived for structs"));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new(input.span(), "Intern needs a struct with named fields"));
+    };
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let interned = Ident::new(&format!("{}Interned", name), Span::call_site());
+
+    let mut interned_generics = input.generics.clone();
+    interned_generics.params = interned_generics
+        .params
+        .into_iter()
+        .filter(|param| !matches!(param, GenericParam::Lifetime(_)))
+        .collect();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let (_, interned_ty_generics, interned_where_clause) = interned_generics.split_for_impl();
+
+    let mut defs = Vec::new();
+    let mut inits = Vec::new();
+    for field in &fields.named {
+        let field_vis = &field.vis;
+        let field_name = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let (def, init) = match kind(ty) {
+            Kind::Owned => {
+                (quote!(::strpool::StrRef), quote!(::strpool::put_heap(self.#field_name)))
+            }
+            Kind::Borrowed => {
+                (quote!(::strpool::StrRef), quote!(::strpool::get_or_intern(self.#field_name)))
+            }
+            Kind::Other => (quote!(#ty), quote!(self.#field_name)),
+        };
+        defs.push(quote!(#field_vis #field_name: #def));
+        inits.push(quote!(#field_name: #init));
+    }
+
+    Ok(quote! {
+        #vis struct #interned #interned_generics #interned_where_clause {
+            #(#defs,)*
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #vis fn intern(self) -> #interned #interned_ty_generics {
+                #interned { #(#inits,)* }
+            }
+        }
+    })
+}
@@ -0,0 +1,45 @@
+use crate::{StrPool, StrRef};
+
+// Codes are the indices of a private pool: dense, starting at 0 and in order
+// of first appearance, and never reassigned.
+#[derive(Default)]
+pub struct DictEncoder {
+    pool: StrPool,
+}
+
+impl DictEncoder {
+    pub fn new() -> DictEncoder {
+        DictEncoder::default()
+    }
+
+    // Panics once there are more distinct values than `u32` codes.
+    pub fn encode(&mut self, str: &str) -> u32 {
+        let StrRef { ptr, .. } = self.pool.get_or_intern(str);
+        u32::try_from(ptr).expect("too many distinct values for u32 codes")
+    }
+
+    // The output is allocated once, and the pool is reserved for every value
+    // being new, so a column of distinct values never rehashes midway. With
+    // repetitive columns the spare capacity is left for later inserts.
+    pub fn encode_column(&mut self, values: &[&str]) -> Vec<u32> {
+        self.pool.reserve(values.len());
+        values.iter().map(|str| self.encode(str)).collect()
+    }
+
+    pub fn decode(&self, code: u32) -> Option<&str> {
+        self.pool.get(StrRef::new(code as usize))
+    }
+
+    // The distinct values, indexed by code.
+    pub fn dictionary(&self) -> impl Iterator<Item = &str> {
+        self.pool.entries_in_order().map(|(_, s)| s)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pool.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pool.is_empty()
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    borrow::Cow,
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{BuildHasher, BuildHasherDefault},
+    ops::Range,
+    str,
+    sync::{Arc, OnceLock},
+};
+
+use crate::{LayeredRef, StrPool, StrRef};
+
+// Binary layout, all integers little-endian:
+//
+//   magic    4 bytes      b"SPFP"
+//   version  u32          `StrPool::FORMAT_VERSION`
+//   count    u32          number of entries
+//   offsets  u32 x count+1  end offset of each entry in `blob`, led by a 0
+//   blob     offsets[count] bytes of UTF-8, entries concatenated in index order
+//
+// Entry `i` is `blob[offsets[i]..offsets[i + 1]]`. Everything is validated once
+// in `from_bytes`, resolution afterwards only slices the bytes.
+const MAGIC: &[u8; 4] = b"SPFP";
+const HEADER_LEN: usize = 12;
+
+type LookupHasher = BuildHasherDefault<DefaultHasher>;
+
+pub struct FrozenPool {
+    bytes: Cow<'static, [u8]>,
+    len: usize,
+    // Content hash to entries, only built once `get_ref` is first used.
+    lookup: OnceLock<HashMap<u64, Vec<usize>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrozenPoolError {
+    Truncated,
+    BadMagic,
+    VersionMismatch { found: u32, expected: u32 },
+    BadOffsets,
+    Utf8(core::str::Utf8Error),
+}
+
+impl core::fmt::Display for FrozenPoolError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FrozenPoolError::Truncated => fmt.write_str("frozen pool data is truncated"),
+            FrozenPoolError::BadMagic => fmt.write_str("not a frozen pool"),
+            FrozenPoolError::VersionMismatch { found, expected } => {
+                write!(fmt, "frozen pool format version {}, expected {}", found, expected)
+            }
+            FrozenPoolError::BadOffsets => fmt.write_str("frozen pool offsets are out of order"),
+            FrozenPoolError::Utf8(err) => err.fmt(fmt),
+        }
+    }
+}
+
+impl std::error::Error for FrozenPoolError {}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<usize> {
+    let word = bytes.get(at..at + 4)?;
+    Some(u32::from_le_bytes(word.try_into().unwrap()) as usize)
+}
+
+fn partition_point(len: usize, pred: impl Fn(usize) -> bool) -> usize {
+    let (mut lo, mut hi) = (0, len);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+impl FrozenPool {
+    // Panics if the pool has more entries or string bytes than the format's
+    // `u32` offsets can address.
+    pub fn from_pool(pool: &StrPool) -> FrozenPool {
+        // Tombstones of removed entries are kept as empty strings, so indices
+        // stay those of the pool.
+        let len = pool.pool.len();
+        let blob_len: usize = pool.pool.iter().map(|s| s.len()).sum();
+        let mut bytes = Vec::with_capacity(HEADER_LEN + 4 * (len + 1) + blob_len);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&StrPool::FORMAT_VERSION.to_le_bytes());
+        let count = u32::try_from(len).expect("too many entries for a frozen pool");
+        bytes.extend_from_slice(&count.to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        let mut end = 0;
+        for s in &pool.pool {
+            end += s.len();
+            let end = u32::try_from(end).expect("too many string bytes for a frozen pool");
+            bytes.extend_from_slice(&end.to_le_bytes());
+        }
+        for s in &pool.pool {
+            bytes.extend_from_slice(s.as_bytes());
+        }
+        FrozenPool { bytes: Cow::Owned(bytes), len, lookup: OnceLock::new() }
+    }
+
+    pub fn from_bytes(bytes: &'static [u8]) -> Result<FrozenPool, FrozenPoolError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(FrozenPoolError::Truncated);
+        }
+        if &bytes[..4] != MAGIC {
+            return Err(FrozenPoolError::BadMagic);
+        }
+        let version = read_u32(bytes, 4).unwrap() as u32;
+        if version != StrPool::FORMAT_VERSION {
+            let expected = StrPool::FORMAT_VERSION;
+            return Err(FrozenPoolError::VersionMismatch { found: version, expected });
+        }
+        let len = read_u32(bytes, 8).unwrap();
+        let blob = (len + 1).checked_mul(4).and_then(|n| n.checked_add(HEADER_LEN));
+        let blob = blob.ok_or(FrozenPoolError::Truncated)?;
+        if bytes.len() < blob {
+            return Err(FrozenPoolError::Truncated);
+        }
+        let mut start = 0;
+        for i in 0..=len {
+            let end = read_u32(bytes, HEADER_LEN + 4 * i).unwrap();
+            if (i == 0 && end != 0) || end < start {
+                return Err(FrozenPoolError::BadOffsets);
+            }
+            let entry = bytes.get(blob + start..blob + end).ok_or(FrozenPoolError::Truncated)?;
+            str::from_utf8(entry).map_err(FrozenPoolError::Utf8)?;
+            start = end;
+        }
+        Ok(FrozenPool { bytes: Cow::Borrowed(bytes), len, lookup: OnceLock::new() })
+    }
+
+    pub fn to_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn range(&self, r: StrRef) -> Option<Range<usize>> {
+        if r.ptr >= self.len {
+            return None;
+        }
+        let offset = |i| read_u32(&self.bytes, HEADER_LEN + 4 * i).unwrap();
+        let blob = HEADER_LEN + 4 * (self.len + 1);
+        Some(blob + offset(r.ptr)..blob + offset(r.ptr + 1))
+    }
+
+    pub fn get(&self, r: StrRef) -> Option<&str> {
+        let entry = &self.bytes[self.range(r)?];
+        Some(unsafe { str::from_utf8_unchecked(entry) })
+    }
+
+    pub fn get_ref(&self, str: &str) -> Option<StrRef> {
+        let lookup = self.lookup.get_or_init(|| {
+            let mut lookup: HashMap<u64, Vec<usize>> = HashMap::new();
+            for ptr in 0..self.len {
+                let hash = LookupHasher::default().hash_one(self.get(StrRef::new(ptr)).unwrap());
+                lookup.entry(hash).or_default().push(ptr);
+            }
+            lookup
+        });
+        let candidates = lookup.get(&LookupHasher::default().hash_one(str))?;
+        let ptr = candidates.iter().copied().find(|&ptr| self.get(StrRef::new(ptr)) == Some(str))?;
+        Some(StrRef::new(ptr))
+    }
+
+    // Finds the entry `str` was resolved from by its address, using a binary
+    // search over the offsets.
+    pub fn ref_of_ptr(&self, str: &str) -> Option<StrRef> {
+        let blob = HEADER_LEN + 4 * (self.len + 1);
+        let start = (str.as_ptr() as usize).checked_sub(self.bytes[blob..].as_ptr() as usize)?;
+        let offset = |i| read_u32(&self.bytes, HEADER_LEN + 4 * i).unwrap();
+        let mut ptr = partition_point(self.len, |i| offset(i) < start);
+        while ptr < self.len && offset(ptr) == start {
+            if offset(ptr + 1) - start == str.len() {
+                return Some(StrRef::new(ptr));
+            }
+            ptr += 1;
+        }
+        None
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+// Entries sorted by content, so lookups binary search instead of hashing.
+// Refs keep the indices they had in the pool this was built from.
+pub struct SortedFrozenPool {
+    entries: Vec<(String, usize)>,
+    // Position in `entries` by original index, `usize::MAX` for gaps the
+    // pool had left.
+    positions: Vec<usize>,
+}
+
+impl StrPool {
+    // Interns entry `r` of `src`. Entries of a pool built by `from_bytes` are
+    // borrowed like static strings, others are copied.
+    pub fn rehome_from(&mut self, src: &FrozenPool, r: StrRef) -> Option<StrRef> {
+        let range = src.range(r)?;
+        Some(match src.bytes {
+            Cow::Borrowed(bytes) => {
+                self.put_static(unsafe { str::from_utf8_unchecked(&bytes[range]) })
+            }
+            Cow::Owned(ref bytes) => {
+                self.get_or_intern(unsafe { str::from_utf8_unchecked(&bytes[range]) })
+            }
+        })
+    }
+
+    pub fn into_sorted(self) -> SortedFrozenPool {
+        let mut entries: Vec<(String, usize)> =
+            self.into_entries().map(|(ptr, s)| (s, ptr)).collect();
+        entries.sort_unstable();
+        let slots = entries.iter().map(|&(_, ptr)| ptr + 1).max().unwrap_or(0);
+        let mut positions = vec![usize::MAX; slots];
+        for (position, &(_, ptr)) in entries.iter().enumerate() {
+            positions[ptr] = position;
+        }
+        SortedFrozenPool { entries, positions }
+    }
+}
+
+impl SortedFrozenPool {
+    // The lowest index holding `str`, if any.
+    pub fn get_ref(&self, str: &str) -> Option<StrRef> {
+        let position = self.entries.partition_point(|(s, _)| s.as_str() < str);
+        match self.entries.get(position) {
+            Some((s, ptr)) if s == str => Some(StrRef::new(*ptr)),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, r: StrRef) -> Option<&str> {
+        let position = *self.positions.get(r.ptr)?;
+        self.entries.get(position).map(|(s, _)| s.as_str())
+    }
+
+    // Entries in content order, with their original refs.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (StrRef, &str)> {
+        self.entries.iter().map(|(s, ptr)| (StrRef::new(*ptr), s.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+// Resolves from a shared read-only base and only stores strings missing from it.
+pub struct OverlayPool {
+    base: Arc<FrozenPool>,
+    overlay: StrPool,
+}
+
+impl OverlayPool {
+    pub fn new(base: Arc<FrozenPool>) -> OverlayPool {
+        OverlayPool { base, overlay: StrPool::default() }
+    }
+
+    pub fn intern(&mut self, str: &str) -> LayeredRef {
+        match self.base.get_ref(str) {
+            Some(r) => LayeredRef::Base(r),
+            None => LayeredRef::Local(self.overlay.get_or_intern(str)),
+        }
+    }
+
+    pub fn resolve(&self, r: LayeredRef) -> Option<&str> {
+        match r {
+            LayeredRef::Base(r) => self.base.get(r),
+            LayeredRef::Local(r) => self.overlay.get(r),
+        }
+    }
+
+    pub fn base(&self) -> &Arc<FrozenPool> {
+        &self.base
+    }
+
+    pub fn overlay(&self) -> &StrPool {
+        &self.overlay
+    }
+}
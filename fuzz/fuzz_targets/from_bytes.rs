@@ -0,0 +1,20 @@
+// Byte input must either intern as is or come back as an error, never panic.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use strpool::{FromBytesError, StrRef};
+
+fuzz_target!(|data: &[u8]| {
+    let valid = core::str::from_utf8(data).ok();
+    let borrowed = StrRef::try_from(data).ok();
+    assert_eq!(borrowed.as_deref(), valid);
+    let owned = StrRef::try_from(data.to_vec()).ok();
+    assert_eq!(owned.as_deref(), valid);
+
+    let max = data.first().map_or(0, |&b| b as usize);
+    match StrRef::from_utf8_capped(data, max) {
+        Ok(r) => assert_eq!(Some(&*r), valid),
+        Err(FromBytesError::TooLong { len, .. }) => assert!(len > max),
+        Err(FromBytesError::Utf8(_)) => assert!(valid.is_none()),
+    }
+});
@@ -0,0 +1,146 @@
+use core::fmt;
+use serde::{
+    de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor},
+    ser::{Serialize, Serializer},
+};
+use serde_json::{Number, Value};
+
+use crate::StrRef;
+
+// Mirror of `serde_json::Value` with strings and object keys interned. Object
+// entries keep their order and numbers are left as they are.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InternedValue {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(StrRef),
+    Array(Vec<InternedValue>),
+    Object(Vec<(StrRef, InternedValue)>),
+}
+
+impl From<&Value> for InternedValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Null => InternedValue::Null,
+            Value::Bool(b) => InternedValue::Bool(*b),
+            Value::Number(n) => InternedValue::Number(n.clone()),
+            Value::String(s) => InternedValue::String(crate::get_or_intern(s)),
+            Value::Array(a) => InternedValue::Array(a.iter().map(InternedValue::from).collect()),
+            Value::Object(o) => InternedValue::Object(
+                o.iter().map(|(k, v)| (crate::get_or_intern(k), InternedValue::from(v))).collect(),
+            ),
+        }
+    }
+}
+
+impl From<&InternedValue> for Value {
+    fn from(value: &InternedValue) -> Self {
+        match value {
+            InternedValue::Null => Value::Null,
+            InternedValue::Bool(b) => Value::Bool(*b),
+            InternedValue::Number(n) => Value::Number(n.clone()),
+            InternedValue::String(s) => Value::String(s.to_string()),
+            InternedValue::Array(a) => Value::Array(a.iter().map(Value::from).collect()),
+            InternedValue::Object(o) => {
+                Value::Object(o.iter().map(|(k, v)| (k.to_string(), Value::from(v))).collect())
+            }
+        }
+    }
+}
+
+impl Serialize for InternedValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            InternedValue::Null => serializer.serialize_unit(),
+            InternedValue::Bool(b) => serializer.serialize_bool(*b),
+            InternedValue::Number(n) => n.serialize(serializer),
+            InternedValue::String(s) => s.serialize(serializer),
+            InternedValue::Array(a) => serializer.collect_seq(a),
+            InternedValue::Object(o) => serializer.collect_map(o.iter().map(|(k, v)| (k, v))),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for InternedValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = InternedValue;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("any JSON value")
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<InternedValue, E> {
+                Ok(InternedValue::Null)
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<InternedValue, E> {
+                Ok(InternedValue::Null)
+            }
+
+            fn visit_some<D>(self, deserializer: D) -> Result<InternedValue, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                InternedValue::deserialize(deserializer)
+            }
+
+            fn visit_bool<E: de::Error>(self, v: bool) -> Result<InternedValue, E> {
+                Ok(InternedValue::Bool(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<InternedValue, E> {
+                Ok(InternedValue::Number(v.into()))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<InternedValue, E> {
+                Ok(InternedValue::Number(v.into()))
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<InternedValue, E> {
+                Ok(Number::from_f64(v).map_or(InternedValue::Null, InternedValue::Number))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<InternedValue, E> {
+                Ok(InternedValue::String(crate::get_or_intern(v)))
+            }
+
+            fn visit_string<E: de::Error>(self, v: String) -> Result<InternedValue, E> {
+                Ok(InternedValue::String(crate::put_heap(v)))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<InternedValue, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(value) = seq.next_element()? {
+                    values.push(value);
+                }
+                Ok(InternedValue::Array(values))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<InternedValue, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(InternedValue::Object(entries))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
@@ -1,64 +1,1897 @@
-use std::{borrow::Cow, ops::Deref, cell::UnsafeCell};
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+
+use std::{
+    borrow::Cow,
+    ops::Deref,
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+    fmt,
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::atomic::{AtomicU64, Ordering},
+};
 use indexmap::IndexSet;
 
-pub struct StrPool {
-    pool: IndexSet<Cow<'static, str>>,
+mod normalize;
+pub use normalize::Normalizer;
+
+mod chunked;
+pub use chunked::ChunkedPool;
+
+mod small;
+pub use small::SmallStrRef;
+
+mod typed;
+pub use typed::{intern, TypedRef};
+
+mod frozen;
+pub use frozen::{FrozenPool, FrozenPoolError, OverlayPool, SortedFrozenPool};
+
+mod arena;
+pub use arena::SyncArenaPool;
+
+mod ref32;
+pub use ref32::{intern32, PoolOverflow, StrRef32};
+
+mod persist;
+
+mod perfect;
+pub use perfect::{codegen, PerfectPool};
+
+mod dict;
+pub use dict::DictEncoder;
+
+mod namespaced;
+pub use namespaced::NamespacedPool;
+
+#[cfg(feature = "allocator_api")]
+mod alloc_pool;
+#[cfg(feature = "allocator_api")]
+pub use alloc_pool::AllocPool;
+
+#[cfg(feature = "derive")]
+pub use strpool_derive::Intern;
+
+#[cfg(feature = "bench")]
+pub mod bench;
+
+#[cfg(all(target_arch = "wasm32", not(target_feature = "atomics")))]
+mod wasm;
+
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "json")]
+pub use json::InternedValue;
+
+#[derive(Default)]
+pub struct StrPool {
+    pool: IndexSet<Entry>,
+    aliases: HashMap<String, usize>,
+    // Caller-chosen keys of `put_canonical`, kept apart from the contents.
+    canonical: HashMap<String, usize>,
+    // Strings of up to 8 bytes, keyed by length and then by their bytes packed
+    // into a `u64`. Authoritative for those lengths, `pool` is not consulted.
+    short: [HashMap<u64, usize>; 9],
+    max_entries: Option<usize>,
+    frozen: bool,
+    strict_policy: Normalizer,
+    on_insert: Option<OnInsert>,
+    input_bytes: u64,
+    stored_bytes: u64,
+    generation: Generation,
+    pushed: usize,
+    // Holders of entries created by `intern_scoped` that are still removable.
+    scoped: HashMap<usize, usize>,
+    dead: usize,
+    empty_policy: EmptyPolicy,
+    index_limit: Option<usize>,
+    #[cfg(feature = "profile")]
+    resolutions: Vec<AtomicU64>,
+}
+
+// `tag` is 0 for deduplicated entries. Each entry added by `push_heap` gets a
+// fresh nonzero tag, so equal content can be stored more than once. Tombstones
+// of removed entries are empty and have `DEAD` set in their tag.
+#[derive(PartialEq, Eq, Hash)]
+struct Entry {
+    str: Cow<'static, str>,
+    tag: usize,
+}
+
+const DEAD: usize = 1 << (usize::BITS - 1);
+
+impl Entry {
+    fn is_dead(&self) -> bool {
+        self.tag & DEAD != 0
+    }
+}
+
+impl Deref for Entry {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.str
+    }
+}
+
+// Finds the deduplicated entry holding a string, hashing the way `Entry` does.
+struct Key<'a>(&'a str);
+
+impl core::hash::Hash for Key<'_> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+        0usize.hash(state);
+    }
+}
+
+impl indexmap::Equivalent<Entry> for Key<'_> {
+    fn equivalent(&self, entry: &Entry) -> bool {
+        entry.tag == 0 && entry.str == self.0
+    }
+}
+
+// Unique across all pools of the process, and renewed whenever entries of a
+// pool are dropped or moved, so cached resolutions can be validated.
+struct Generation(u64);
+
+impl Generation {
+    fn next() -> Generation {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Generation(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for Generation {
+    fn default() -> Self {
+        Generation::next()
+    }
+}
+
+// Bounded so that pools stay `Send` and `Sync`.
+pub type OnInsert = Box<dyn FnMut(StrRef, &str) + Send + Sync>;
+
+// What interning `""` yields.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EmptyPolicy {
+    // The entry of `""`, like any other string.
+    #[default]
+    Share,
+    // Panics in `put_*`, for pools that must never see empty strings. The
+    // fallible `try_put_*` calls return `TryPutError::Empty` instead.
+    Error,
+    // `StrRef::NONE`, which resolves to nothing, and no entry is added.
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolFull;
+
+impl core::fmt::Display for PoolFull {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fmt.write_str("string pool is full")
+    }
+}
+
+impl std::error::Error for PoolFull {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryPutError {
+    Full(PoolFull),
+    Overflow(PoolOverflow),
+    // `""` put into a pool with `EmptyPolicy::Error`.
+    Empty,
+}
+
+impl From<PoolFull> for TryPutError {
+    fn from(err: PoolFull) -> Self {
+        TryPutError::Full(err)
+    }
+}
+
+impl From<PoolOverflow> for TryPutError {
+    fn from(err: PoolOverflow) -> Self {
+        TryPutError::Overflow(err)
+    }
+}
+
+impl core::fmt::Display for TryPutError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TryPutError::Full(err) => err.fmt(fmt),
+            TryPutError::Overflow(err) => err.fmt(fmt),
+            TryPutError::Empty => fmt.write_str("empty string interned with EmptyPolicy::Error"),
+        }
+    }
+}
+
+impl std::error::Error for TryPutError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TryPutError::Full(err) => Some(err),
+            TryPutError::Overflow(err) => Some(err),
+            TryPutError::Empty => None,
+        }
+    }
+}
+
+// Skips tombstones, the second field counts the entries left.
+pub struct IntoIter(indexmap::set::IntoIter<Entry>, usize);
+
+impl Iterator for IntoIter {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let entry = self.0.find(|entry| !entry.is_dead())?;
+        self.1 -= 1;
+        Some(entry.str.into_owned())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.1, Some(self.1))
+    }
+}
+
+impl DoubleEndedIterator for IntoIter {
+    fn next_back(&mut self) -> Option<String> {
+        let entry = self.0.rfind(|entry| !entry.is_dead())?;
+        self.1 -= 1;
+        Some(entry.str.into_owned())
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+impl IntoIterator for StrPool {
+    type Item = String;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        let len = self.len();
+        IntoIter(self.pool.into_iter(), len)
+    }
+}
+
+// Maps refs issued before a compaction to their new index, or to nothing if
+// their entry was dropped.
+#[derive(Debug, Clone, Default)]
+pub struct Remap {
+    map: Vec<Option<usize>>,
+}
+
+impl Remap {
+    pub fn get(&self, r: &StrRef) -> Option<StrRef> {
+        self.map.get(r.ptr).copied().flatten().map(StrRef::new)
+    }
+}
+
+// A ref into one of two pools layered on top of each other, the shared base
+// pool or the local one.
+#[derive(Clone)]
+pub enum LayeredRef {
+    Base(StrRef),
+    Local(StrRef),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoolDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+// The contents of a pool at one point, to compare against later by index.
+// Static entries are not copied.
+#[derive(Clone)]
+pub struct Snapshot {
+    generation: u64,
+    entries: Vec<Option<Cow<'static, str>>>,
+}
+
+// `modified` carries the content each entry had at the snapshot.
+#[derive(Clone, Default)]
+pub struct Changes {
+    pub added: Vec<StrRef>,
+    pub modified: Vec<(StrRef, String)>,
+    pub removed: Vec<StrRef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizationError {
+    pub input: String,
+    pub normalized: String,
+}
+
+impl core::fmt::Display for NormalizationError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(fmt, "{:?} is not normalized, expected {:?}", self.input, self.normalized)
+    }
+}
+
+impl std::error::Error for NormalizationError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FromBytesError {
+    TooLong { len: usize, max: usize },
+    Utf8(core::str::Utf8Error),
+}
+
+impl core::fmt::Display for FromBytesError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FromBytesError::TooLong { len, max } => {
+                write!(fmt, "{} bytes exceed the cap of {} bytes", len, max)
+            }
+            FromBytesError::Utf8(err) => err.fmt(fmt),
+        }
+    }
+}
+
+impl std::error::Error for FromBytesError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FromBytesError::TooLong { .. } => None,
+            FromBytesError::Utf8(err) => Some(err),
+        }
+    }
+}
+
+const RESOLVE_CACHE_LEN: usize = 16;
+
+type ResolveCacheSlot = Cell<Option<(u64, usize, *const str)>>;
+
+thread_local! {
+    #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    static GLOBAL_POOL: std::cell::UnsafeCell<StrPool> = Default::default();
+    // Set while the global pool's insert hook runs, see `run_hook`.
+    #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    static IN_HOOK: Cell<bool> = const { Cell::new(false) };
+    // Direct-mapped by index: (pool generation, index, resolved slice).
+    static RESOLVE_CACHE: [ResolveCacheSlot; RESOLVE_CACHE_LEN] =
+        const { [const { Cell::new(None) }; RESOLVE_CACHE_LEN] };
+}
+
+#[derive(Clone)]
+pub struct StrRef {
+    ptr: usize,
+    // The thread the ref was handed out on, checked on every resolution
+    // through the global pool. `None` for refs built from raw indices.
+    #[cfg(debug_assertions)]
+    thread: Option<std::thread::ThreadId>,
+}
+
+fn pack(str: &str) -> Option<(usize, u64)> {
+    let len = str.len();
+    if len > 8 {
+        return None;
+    }
+    let mut buf = [0; 8];
+    buf[..len].copy_from_slice(str.as_bytes());
+    Some((len, u64::from_le_bytes(buf)))
+}
+
+impl StrPool {
+    // Version of the serialized pool formats, bumped on any layout change.
+    pub const FORMAT_VERSION: u32 = 1;
+
+    // Keyword `i` ends up at index `i` unless it repeats an earlier one, so the
+    // indices can be spelled out as constants, e.g.
+    // `const ELSE: StrRef = StrRef::from_raw(1);` for `&["if", "else"]`.
+    pub fn from_static_slice(keywords: &'static [&'static str]) -> StrPool {
+        let mut pool = StrPool::default();
+        pool.pool.reserve(keywords.len());
+        for keyword in keywords {
+            pool.put_static(keyword);
+        }
+        pool
+    }
+
+    // Panics on `""` under `EmptyPolicy::Error`, as do the other `put_*` calls.
+    pub fn put_static(&mut self, str: &'static str) -> StrRef {
+        let (ptr, _) = self.insert(Cow::Borrowed(str));
+        // println!("put_static: '{}' -> {} new={}", str, ptr, new);
+        StrRef::new(ptr)
+    }
+
+    pub fn put_heap(&mut self, str: String) -> StrRef {
+        // print!("put_heap: '{}'", str);
+        let (ptr, _) = self.insert(Cow::Owned(str));
+        // println!(" -> {} new={}", ptr, new);
+        StrRef::new(ptr)
+    }
+
+    // `Box<str>` turns into a `String` in place, so on a miss the entry keeps
+    // the box's allocation.
+    pub fn put_boxed(&mut self, str: Box<str>) -> StrRef {
+        let (ptr, _) = self.insert(Cow::Owned(str.into_string()));
+        StrRef::new(ptr)
+    }
+
+    fn lookup(&self, str: &str) -> Option<usize> {
+        if !self.aliases.is_empty() {
+            if let Some(&ptr) = self.aliases.get(str) {
+                return Some(ptr);
+            }
+        }
+        if let Some((len, packed)) = pack(str) {
+            return self.short[len].get(&packed).copied();
+        }
+        self.pool.get_index_of(&Key(str))
+    }
+
+    fn empty(&self) -> Option<usize> {
+        match self.empty_policy {
+            EmptyPolicy::Share => None,
+            EmptyPolicy::Error => panic!("empty string interned with EmptyPolicy::Error"),
+            EmptyPolicy::None => Some(StrRef::NONE.ptr),
+        }
+    }
+
+    fn insert(&mut self, str: Cow<'static, str>) -> (usize, bool) {
+        if str.is_empty() {
+            if let Some(ptr) = self.empty() {
+                return (ptr, false);
+            }
+        }
+        let len = str.len() as u64;
+        self.input_bytes += len;
+        if !self.aliases.is_empty() {
+            if let Some(&ptr) = self.aliases.get(&*str) {
+                self.pin(ptr);
+                return (ptr, false);
+            }
+        }
+        let packed = pack(&str);
+        if let Some((n, key)) = packed {
+            if let Some(&ptr) = self.short[n].get(&key) {
+                self.pin(ptr);
+                return (ptr, false);
+            }
+        }
+        if self.frozen {
+            let ptr = self.pool.get_index_of(&Key(&str));
+            let ptr = ptr.expect("new string interned into a frozen pool");
+            self.pin(ptr);
+            return (ptr, false);
+        }
+        let (ptr, new) = self.pool.insert_full(Entry { str, tag: 0 });
+        if !new {
+            self.pin(ptr);
+        }
+        if new {
+            if let Some((n, key)) = packed {
+                self.short[n].insert(key, ptr);
+            }
+            self.inserted(ptr);
+        }
+        (ptr, new)
+    }
+
+    fn inserted(&mut self, ptr: usize) {
+        #[cfg(feature = "profile")]
+        self.resolutions.push(AtomicU64::new(0));
+        self.stored_bytes += self.pool[ptr].len() as u64;
+        #[cfg(feature = "tracing")]
+        {
+            tracing::trace!(index = ptr, len = self.pool[ptr].len(), "interned new string");
+            // Gauges whenever the pool doubles.
+            let entries = self.len();
+            if entries.is_power_of_two() {
+                tracing::debug!(len = entries, capacity = self.capacity(), "string pool grew");
+            }
+        }
+        if let Some(on_insert) = &mut self.on_insert {
+            on_insert(StrRef::new(ptr), &self.pool[ptr]);
+        }
+    }
+
+    // Always appends a new entry, even if equal content is already interned,
+    // so equal strings no longer imply equal indices. Refs still compare
+    // equal by content. Pushed entries are never found by lookups, they are
+    // only reachable through the returned ref.
+    pub fn push_heap(&mut self, str: String) -> StrRef {
+        if str.is_empty() {
+            if let Some(ptr) = self.empty() {
+                return StrRef::new(ptr);
+            }
+        }
+        assert!(!self.frozen, "new string pushed into a frozen pool");
+        self.input_bytes += str.len() as u64;
+        self.pushed += 1;
+        let (ptr, _) = self.pool.insert_full(Entry { str: Cow::Owned(str), tag: self.pushed });
+        self.inserted(ptr);
+        StrRef::new(ptr)
+    }
+
+    fn hit(&mut self, str: &str) -> Option<StrRef> {
+        if str.is_empty() {
+            if let Some(ptr) = self.empty() {
+                return Some(StrRef::new(ptr));
+            }
+        }
+        let ptr = self.lookup(str)?;
+        self.input_bytes += str.len() as u64;
+        self.pin(ptr);
+        Some(StrRef::new(ptr))
+    }
+
+    // Handing out a scoped entry through any other path makes it permanent.
+    fn pin(&mut self, ptr: usize) {
+        if !self.scoped.is_empty() {
+            self.scoped.remove(&ptr);
+        }
+    }
+
+    fn intern_scoped(&mut self, str: &str) -> usize {
+        match self.lookup(str) {
+            Some(ptr) => {
+                self.input_bytes += str.len() as u64;
+                if let Some(holders) = self.scoped.get_mut(&ptr) {
+                    *holders += 1;
+                }
+                ptr
+            }
+            None => {
+                let (ptr, new) = self.insert(Cow::Owned(str.to_owned()));
+                if new {
+                    self.scoped.insert(ptr, 1);
+                }
+                ptr
+            }
+        }
+    }
+
+    fn release_scoped(&mut self, ptr: usize) {
+        let Some(holders) = self.scoped.get_mut(&ptr) else { return };
+        *holders -= 1;
+        if *holders == 0 {
+            self.scoped.remove(&ptr);
+            self.remove_in_place(ptr);
+        }
+    }
+
+    // Drops an entry without renumbering the others. Its slot is left as a
+    // tombstone that resolves to nothing, until the next compaction or reset
+    // drops it too. Clones of the ref may still have slices of it out, so the
+    // buffer is leaked, not freed.
+    fn remove_in_place(&mut self, ptr: usize) {
+        let entry = &self.pool[ptr];
+        if let Some((len, packed)) = pack(entry).filter(|_| entry.tag == 0) {
+            self.short[len].remove(&packed);
+        }
+        self.aliases.retain(|_, alias| *alias != ptr);
+        self.canonical.retain(|_, canonical| *canonical != ptr);
+        self.pushed += 1;
+        let tombstone = Entry { str: Cow::Borrowed(""), tag: DEAD | self.pushed };
+        if let Ok(old) = self.pool.replace_index(ptr, tombstone) {
+            self.stored_bytes -= old.len() as u64;
+            self.dead += 1;
+            core::mem::forget(old);
+        }
+        #[cfg(feature = "profile")]
+        self.resolutions[ptr].store(0, Ordering::Relaxed);
+        RESOLVE_CACHE.with(|cache| cache[ptr % RESOLVE_CACHE_LEN].set(None));
+    }
+
+    // Changes what `r` resolves to for every holder of it, keeping its index.
+    // If `new` is interned already, nothing changes and its existing ref is
+    // returned instead, while `r` keeps resolving to the old content.
+    pub fn rename(&mut self, r: StrRef, new: String) -> StrRef {
+        let entry = self.entry(r.ptr).expect("null string ref");
+        if let Some(ptr) = self.lookup(&new) {
+            return StrRef::new(ptr);
+        }
+        assert!(!self.frozen, "new string interned into a frozen pool");
+        let tag = entry.tag;
+        if let Some((len, packed)) = pack(entry).filter(|_| tag == 0) {
+            self.short[len].remove(&packed);
+        }
+        if let Some((len, packed)) = pack(&new).filter(|_| tag == 0) {
+            self.short[len].insert(packed, r.ptr);
+        }
+        self.stored_bytes += new.len() as u64;
+        if let Ok(old) = self.pool.replace_index(r.ptr, Entry { str: Cow::Owned(new), tag }) {
+            self.stored_bytes -= old.len() as u64;
+        }
+        RESOLVE_CACHE.with(|cache| cache[r.ptr % RESOLVE_CACHE_LEN].set(None));
+        r
+    }
+
+    // Reserves once for the whole load, then inserts in input order, so
+    // indices follow first appearance as with any other insert. Refs come
+    // back in input order.
+    pub fn bulk_load(&mut self, strings: Vec<String>) -> Vec<StrRef> {
+        self.pool.reserve(strings.len());
+        strings.into_iter().map(|str| self.put_heap(str)).collect()
+    }
+
+    // Interning `alias` afterwards yields the entry of `canonical`. This takes
+    // precedence over an entry that already holds `alias` itself: refs issued
+    // for that entry before stay valid, but are no longer handed out.
+    pub fn add_alias(&mut self, alias: &str, canonical: &str) {
+        let StrRef { ptr, .. } = self.get_or_intern(canonical);
+        self.aliases.insert(alias.to_owned(), ptr);
+    }
+
+    // Deduplicates by `key` instead of content: the first `display` put for
+    // a key is what all later calls with that key get back. The entry itself
+    // is a regular one, interning `display` by content finds it too.
+    pub fn put_canonical(&mut self, key: &str, display: String) -> StrRef {
+        if let Some(&ptr) = self.canonical.get(key) {
+            self.input_bytes += display.len() as u64;
+            return StrRef::new(ptr);
+        }
+        let r = self.put_heap(display);
+        self.canonical.insert(key.to_owned(), r.ptr);
+        r
+    }
+
+    pub fn get_or_intern(&mut self, str: &str) -> StrRef {
+        match self.hit(str) {
+            Some(r) => r,
+            None => self.put_heap(str.to_owned()),
+        }
+    }
+
+    // Only allocates if the string is new and was not borrowed.
+    fn put_cow(&mut self, str: Cow<'_, str>) -> StrRef {
+        match str {
+            Cow::Borrowed(str) => self.get_or_intern(str),
+            Cow::Owned(str) => self.put_heap(str),
+        }
+    }
+
+    pub fn put_normalized(&mut self, str: &str, normalizer: &Normalizer) -> StrRef {
+        self.put_cow(normalizer.apply(str))
+    }
+
+    pub fn put_heap_or_borrow(&mut self, str: String, base: &StrPool) -> LayeredRef {
+        match base.lookup(&str) {
+            Some(ptr) => LayeredRef::Base(StrRef::new(ptr)),
+            None => LayeredRef::Local(self.put_heap(str)),
+        }
+    }
+
+    pub fn get_layered<'a>(&'a self, r: LayeredRef, base: &'a StrPool) -> Option<&'a str> {
+        match r {
+            LayeredRef::Base(r) => base.get(r),
+            LayeredRef::Local(r) => self.get(r),
+        }
+    }
+
+    // Only affects interning from now on, an existing `""` entry stays.
+    pub fn set_empty_policy(&mut self, policy: EmptyPolicy) {
+        self.empty_policy = policy;
+    }
+
+    pub fn empty_policy(&self) -> EmptyPolicy {
+        self.empty_policy
+    }
+
+    // Called for every new entry, but not for strings that were already
+    // interned.
+    pub fn set_on_insert(&mut self, on_insert: OnInsert) {
+        self.on_insert = Some(on_insert);
+    }
+
+    // Once frozen, existing strings still resolve and can be looked up by
+    // `put_*`, but interning a new one panics.
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    pub fn set_strict_policy(&mut self, policy: Normalizer) {
+        self.strict_policy = policy;
+    }
+
+    // Rejects input the strict policy would change instead of normalizing it.
+    pub fn put_strict(&mut self, str: String) -> Result<StrRef, NormalizationError> {
+        let normalized = self.strict_policy.apply(&str);
+        if *normalized != *str {
+            let normalized = normalized.into_owned();
+            return Err(NormalizationError { input: str, normalized });
+        }
+        Ok(self.put_heap(str))
+    }
+
+    // Caps the number of distinct entries. Only checked by the `try_put_*`
+    // methods, `put_*` always inserts.
+    pub fn set_max_entries(&mut self, max_entries: Option<usize>) {
+        self.max_entries = max_entries;
+    }
+
+    pub fn max_entries(&self) -> Option<usize> {
+        self.max_entries
+    }
+
+    // Indices the fallible `try_put_*` calls may hand out, by default those
+    // that fit a `StrRef32`. Lowering it is mostly useful to test the limit.
+    pub fn set_index_limit(&mut self, limit: Option<usize>) {
+        self.index_limit = limit;
+    }
+
+    pub fn index_limit(&self) -> usize {
+        self.index_limit.unwrap_or((u32::MAX as usize).saturating_add(1))
+    }
+
+    // Checked before a new entry is added, lookups of existing ones never fail.
+    fn check_room(&self) -> Result<(), TryPutError> {
+        if self.max_entries.is_some_and(|max_entries| self.len() >= max_entries) {
+            return Err(PoolFull.into());
+        }
+        if self.pool.len() >= self.index_limit() {
+            return Err(PoolOverflow.into());
+        }
+        Ok(())
+    }
+
+    fn check_empty(&self, str: &str) -> Result<(), TryPutError> {
+        if str.is_empty() && self.empty_policy == EmptyPolicy::Error {
+            return Err(TryPutError::Empty);
+        }
+        Ok(())
+    }
+
+    pub fn try_put_heap(&mut self, str: String) -> Result<StrRef, TryPutError> {
+        self.check_empty(&str)?;
+        if let Some(r) = self.hit(&str) {
+            return Ok(r);
+        }
+        self.check_room()?;
+        Ok(self.put_heap(str))
+    }
+
+    pub fn try_put_static(&mut self, str: &'static str) -> Result<StrRef, TryPutError> {
+        self.check_empty(str)?;
+        if let Some(r) = self.hit(str) {
+            return Ok(r);
+        }
+        self.check_room()?;
+        Ok(self.put_static(str))
+    }
+
+    pub fn get(&self, r: StrRef) -> Option<&str> {
+        let s = self.entry(r.ptr).map(|entry| &*entry.str);
+        self.record(r.ptr);
+        // println!("get: {} -> {:?}", r.ptr, s);
+        s
+    }
+
+    // Bytes passed to any `put_*` call so far, and the bytes actually stored.
+    pub fn savings(&self) -> (u64, u64) {
+        (self.input_bytes, self.stored_bytes)
+    }
+
+    // Matches by address and length rather than content. The entry holding
+    // the content is looked up first, which is all it takes unless the pool
+    // has had entries pushed by `push_heap` or removed: those are only found
+    // by a scan over all entries.
+    pub fn ref_of_ptr(&self, str: &str) -> Option<StrRef> {
+        let same = |s: &str| core::ptr::eq(s.as_ptr(), str.as_ptr()) && s.len() == str.len();
+        if let Some(ptr) = self.pool.get_index_of(&Key(str)) {
+            if same(&self.pool[ptr]) {
+                return Some(StrRef::new(ptr));
+            }
+        }
+        if self.pushed == 0 {
+            return None;
+        }
+        let (ptr, _) = self.entries_in_order().find(|&(_, s)| same(s))?;
+        Some(StrRef::new(ptr))
+    }
+
+    pub fn get_cow(&self, r: StrRef) -> Option<&Cow<'static, str>> {
+        self.entry(r.ptr).map(|entry| &entry.str)
+    }
+
+    #[inline]
+    fn record(&self, _ptr: usize) {
+        #[cfg(feature = "profile")]
+        if let Some(count) = self.resolutions.get(_ptr) {
+            count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // The `n` most resolved entries with their resolution counts, most
+    // resolved first. Counting needs the `profile` feature.
+    #[cfg(feature = "profile")]
+    pub fn hot_entries(&self, n: usize) -> Vec<(StrRef, &str, u64)> {
+        let mut hot: Vec<_> = self
+            .entries_in_order()
+            .map(|(ptr, s)| (StrRef::new(ptr), s, self.resolutions[ptr].load(Ordering::Relaxed)))
+            .collect();
+        hot.sort_by_key(|&(_, _, count)| core::cmp::Reverse(count));
+        hot.truncate(n);
+        hot
+    }
+
+    fn ordinal(&self, ptr: usize) -> Option<usize> {
+        self.entry(ptr)?;
+        if self.dead == 0 {
+            return Some(ptr);
+        }
+        Some(self.pool.iter().take(ptr).filter(|entry| !entry.is_dead()).count())
+    }
+
+    fn entry(&self, ptr: usize) -> Option<&Entry> {
+        self.pool.get_index(ptr).filter(|entry| !entry.is_dead())
+    }
+
+    pub fn is_static(&self, r: StrRef) -> Option<bool> {
+        self.entry(r.ptr).map(|entry| matches!(entry.str, Cow::Borrowed(_)))
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        let entries = self.pool.iter().map(|entry| (!entry.is_dead()).then(|| entry.str.clone()));
+        Snapshot { generation: self.generation.0, entries: entries.collect() }
+    }
+
+    // Entries appended, renamed or removed in place since `snapshot`. Panics
+    // if a compaction or reset has renumbered entries in between, refs from
+    // before have to be remapped then instead.
+    pub fn changes_since(&self, snapshot: &Snapshot) -> Changes {
+        assert_eq!(self.generation.0, snapshot.generation, "pool was compacted since snapshot");
+        let mut changes = Changes::default();
+        for (ptr, entry) in self.pool.iter().enumerate() {
+            let r = StrRef::new(ptr);
+            match (snapshot.entries.get(ptr), entry.is_dead()) {
+                (None | Some(None), false) => changes.added.push(r),
+                (Some(Some(old)), false) if *old != entry.str => {
+                    changes.modified.push((r, old.to_string()))
+                }
+                (Some(Some(_)), true) => changes.removed.push(r),
+                _ => {}
+            }
+        }
+        changes
+    }
+
+    // Compares by content only, indices of both pools are ignored.
+    pub fn diff(&self, new: &StrPool) -> PoolDiff {
+        let missing = |a: &StrPool, b: &StrPool| {
+            let b: HashSet<&str> = b.entries_in_order().map(|(_, s)| s).collect();
+            let missing = a.entries_in_order().filter(|(_, s)| !b.contains(s));
+            missing.map(|(_, s)| s.to_owned()).collect()
+        };
+        PoolDiff { added: missing(new, self), removed: missing(self, new) }
+    }
+
+    fn retain_indices(&mut self, mut keep: impl FnMut(usize, &Cow<'static, str>) -> bool) -> Remap {
+        let mut next = 0;
+        let map: Vec<_> = self
+            .pool
+            .iter()
+            .enumerate()
+            .map(|(ptr, entry)| {
+                (!entry.is_dead() && keep(ptr, &entry.str)).then(|| {
+                    next += 1;
+                    next - 1
+                })
+            })
+            .collect();
+        let mut flags = map.iter();
+        self.pool.retain(|_| flags.next().unwrap().is_some());
+        #[cfg(feature = "profile")]
+        {
+            let mut flags = map.iter();
+            self.resolutions.retain(|_| flags.next().unwrap().is_some());
+        }
+        self.generation = Generation::next();
+        self.scoped.clear();
+        self.dead = 0;
+        let remap = |_: &String, ptr: &mut usize| match map.get(*ptr).copied().flatten() {
+            Some(new) => {
+                *ptr = new;
+                true
+            }
+            None => false,
+        };
+        self.aliases.retain(remap);
+        self.canonical.retain(remap);
+        self.short = Default::default();
+        for (ptr, entry) in self.pool.iter().enumerate().filter(|(_, entry)| entry.tag == 0) {
+            if let Some((len, packed)) = pack(entry) {
+                self.short[len].insert(packed, ptr);
+            }
+        }
+        Remap { map }
+    }
+
+    // Keeps only `'static` entries, which cost no heap.
+    pub fn reset_heap_only(&mut self) -> Remap {
+        self.retain_indices(|_, s| matches!(s, Cow::Borrowed(_)))
+    }
+
+    // Turns a heap entry into a static one by leaking its buffer in place, so
+    // slices of it stay valid even if the entry is later dropped.
+    fn leak(&mut self, ptr: usize) -> Option<&'static str> {
+        let entry = self.entry(ptr)?;
+        let leaked: &'static str = match &entry.str {
+            Cow::Borrowed(s) => return Some(s),
+            Cow::Owned(s) => unsafe { &*(s.as_str() as *const str) },
+        };
+        let tag = entry.tag;
+        let old = self.pool.replace_index(ptr, Entry { str: Cow::Borrowed(leaked), tag });
+        core::mem::forget(old);
+        Some(leaked)
+    }
+
+    // Live refs are matched by index, entries no ref points to are dropped.
+    // Taking them as an iterator rather than a `HashSet<StrRef>` keeps equal
+    // `push_heap` entries apart, and works for refs of pools but the global.
+    pub fn compact<'a>(&mut self, live: impl IntoIterator<Item = &'a StrRef>) -> Remap {
+        let live: HashSet<usize> = live.into_iter().map(|r| r.ptr).collect();
+        self.retain_indices(|ptr, _| live.contains(&ptr))
+    }
+
+    // Every entry holding `str`, which is more than one only for entries added
+    // by `push_heap`. Scans all entries.
+    pub fn refs_for(&self, str: &str) -> Vec<StrRef> {
+        let entries = self.entries_in_order().filter(|&(_, s)| s == str);
+        entries.map(|(ptr, _)| StrRef::new(ptr)).collect()
+    }
+
+    // The entries `compact` would drop for the same `live` refs.
+    pub fn unreferenced<'a>(
+        &self,
+        live: impl IntoIterator<Item = &'a StrRef>,
+    ) -> Vec<(StrRef, &str)> {
+        let live: HashSet<usize> = live.into_iter().map(|r| r.ptr).collect();
+        let entries = self.entries_in_order().filter(|(ptr, _)| !live.contains(ptr));
+        entries.map(|(ptr, s)| (StrRef::new(ptr), s)).collect()
+    }
+
+    // Guaranteed to yield entries in insertion order, which is also index
+    // order, independent of the hasher. Compaction and resets keep the
+    // relative order of surviving entries.
+    pub fn entries_in_order(&self) -> impl Iterator<Item = (usize, &str)> {
+        let entries = self.pool.iter().enumerate().filter(|(_, entry)| !entry.is_dead());
+        entries.map(|(ptr, entry)| (ptr, &*entry.str))
+    }
+
+    // `(ref, is_static, len)` of every entry, in index order.
+    pub fn entry_meta(&self) -> impl Iterator<Item = (StrRef, bool, usize)> + '_ {
+        let entries = self.pool.iter().enumerate().filter(|(_, entry)| !entry.is_dead());
+        entries.map(|(ptr, entry)| {
+            (StrRef::new(ptr), matches!(entry.str, Cow::Borrowed(_)), entry.str.len())
+        })
+    }
+
+    pub fn length_histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for (_, s) in self.entries_in_order() {
+            *histogram.entry(s.len()).or_default() += 1;
+        }
+        histogram
+    }
+
+    // Buckets are keyed by the smallest power of two not less than the length,
+    // with empty strings counted under 0.
+    pub fn length_histogram_pow2(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for (_, s) in self.entries_in_order() {
+            let bucket = if s.is_empty() { 0 } else { s.len().next_power_of_two() };
+            *histogram.entry(bucket).or_default() += 1;
+        }
+        histogram
+    }
+
+    // Pairs of entries that only differ in ASCII case or whitespace, each
+    // paired with the earliest entry of its group. Whitespace runs inside a
+    // string compare as a single space, leading and trailing ones not at all.
+    pub fn near_duplicates(&self) -> Vec<(StrRef, StrRef)> {
+        let mut first: HashMap<String, usize> = HashMap::new();
+        let mut pairs = Vec::new();
+        for (ptr, s) in self.entries_in_order() {
+            let words: Vec<_> = s.split_whitespace().map(str::to_ascii_lowercase).collect();
+            let key = words.join(" ");
+            match first.get(&key) {
+                Some(&earliest) => pairs.push((StrRef::new(earliest), StrRef::new(ptr))),
+                None => {
+                    first.insert(key, ptr);
+                }
+            }
+        }
+        pairs
+    }
+
+    pub fn into_entries(self) -> impl Iterator<Item = (usize, String)> {
+        let entries = self.pool.into_iter().enumerate();
+        let entries = entries.filter(|(_, entry)| !entry.is_dead());
+        entries.map(|(ptr, entry)| (ptr, entry.str.into_owned()))
+    }
+
+    // One past the highest index handed out, i.e. the bits a bitset keyed by
+    // `bit_index` needs. Slots of removed entries are included.
+    pub fn max_index(&self) -> usize {
+        self.pool.len()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.pool.reserve(additional);
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.pool.shrink_to_fit();
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.pool.capacity()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pool.len() - self.dead
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Deref for StrRef {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.resolve().expect("null string ref")
+    }
+}
+
+impl StrRef {
+    fn new(ptr: usize) -> StrRef {
+        StrRef {
+            ptr,
+            #[cfg(debug_assertions)]
+            thread: Some(std::thread::current().id()),
+        }
+    }
+
+    fn resolve<'a>(&self) -> Option<&'a str> {
+        #[cfg(debug_assertions)]
+        if let Some(thread) = self.thread {
+            let current = std::thread::current();
+            assert!(
+                thread == current.id(),
+                "StrRef created on thread {:?} resolved on thread {:?} ({}), \
+                 refs only resolve in the global pool of the thread that made them",
+                thread,
+                current.id(),
+                current.name().unwrap_or("unnamed"),
+            );
+        }
+        let pool = unsafe { global() };
+        let generation = pool.generation.0;
+        RESOLVE_CACHE.with(|cache| {
+            let slot = &cache[self.ptr % RESOLVE_CACHE_LEN];
+            if let Some((cached_generation, ptr, str)) = slot.get() {
+                if cached_generation == generation && ptr == self.ptr {
+                    pool.record(ptr);
+                    return Some(unsafe { &*str });
+                }
+            }
+            let str = pool.get(self.clone())?;
+            slot.set(Some((generation, self.ptr, str)));
+            Some(str)
+        })
+    }
+
+    // Entries can be dropped by compactions and resets, so a heap entry is
+    // turned into a static one first, leaking its buffer. Static entries are
+    // returned as they are, nothing is copied either way.
+    pub fn leak(&self) -> &'static str {
+        unsafe { global() }.leak(self.ptr).expect("null string ref")
+    }
+
+    // Equal indices imply equal content within a pool. The converse does not
+    // hold for `push_heap` entries, so this is a fast path, not `==`.
+    pub fn same_index(&self, other: &StrRef) -> bool {
+        self.ptr == other.ptr
+    }
+
+    // Whether the ref names a live entry of this thread's global pool. Only
+    // debug builds remember the thread a ref was made on, otherwise a ref from
+    // another thread's pool is only caught if its index is out of bounds here,
+    // it may as well name an unrelated entry.
+    pub fn is_resolvable(&self) -> bool {
+        #[cfg(debug_assertions)]
+        if self.thread.is_some_and(|thread| thread != std::thread::current().id()) {
+            return false;
+        }
+        self.try_resolve().is_some()
+    }
+
+    // Position of the entry in insertion order among the entries currently in
+    // the pool. Unlike `as_raw`, which is opaque, this is meant to be relied on,
+    // e.g. to serialize entries in a stable order. Compactions and resets
+    // renumber ordinals but keep their relative order. Slots of removed
+    // entries are skipped, which takes a scan while the pool has any.
+    pub fn ordinal(&self) -> usize {
+        unsafe { global() }.ordinal(self.ptr).expect("null string ref")
+    }
+
+    // The index itself, meant to be used as a bit position: indices of a pool
+    // stay below its `max_index`. Unlike ordinals they are not renumbered when
+    // an entry is removed in place, so a bitset may have unused bits.
+    pub fn bit_index(&self) -> usize {
+        self.ptr
+    }
+
+    pub fn try_resolve(&self) -> Option<&str> {
+        self.resolve()
+    }
+
+    // Never names an entry, see `EmptyPolicy::None`.
+    pub const NONE: StrRef = StrRef {
+        ptr: usize::MAX,
+        #[cfg(debug_assertions)]
+        thread: None,
+    };
+
+    // Not tied to a thread, as the index could come from anywhere.
+    pub const fn from_raw(ptr: usize) -> StrRef {
+        StrRef {
+            ptr,
+            #[cfg(debug_assertions)]
+            thread: None,
+        }
+    }
+
+    pub const fn as_raw(&self) -> usize {
+        self.ptr
+    }
+
+    pub fn is_static(&self) -> bool {
+        unsafe { global() }.is_static(self.clone()).expect("null string ref")
+    }
+
+    // `buf` is cleared and reused as scratch space, so a loop passing the same
+    // buffer only allocates for strings that were not interned before.
+    pub fn from_display_buf<T: fmt::Display + ?Sized>(value: &T, buf: &mut String) -> StrRef {
+        use fmt::Write;
+        buf.clear();
+        write!(buf, "{}", value).expect("a Display implementation returned an error");
+        get_or_intern(buf)
+    }
+
+    pub fn from_reader<R: std::io::Read>(reader: R, max: usize) -> std::io::Result<StrRef> {
+        use std::io::{Error, ErrorKind, Read};
+        let mut buf = Vec::new();
+        reader.take((max as u64).saturating_add(1)).read_to_end(&mut buf)?;
+        if buf.len() > max {
+            return Err(Error::new(ErrorKind::InvalidData, "stream exceeds size cap"));
+        }
+        let buf = String::from_utf8(buf)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err.utf8_error()))?;
+        Ok(put_heap(buf))
+    }
+
+    // For untrusted input: the length is checked before anything else, and
+    // nothing is allocated unless the bytes are valid UTF-8 and not interned
+    // yet. Never panics on any input, unless the global pool is frozen.
+    pub fn from_utf8_capped(bytes: &[u8], max: usize) -> Result<StrRef, FromBytesError> {
+        if bytes.len() > max {
+            return Err(FromBytesError::TooLong { len: bytes.len(), max });
+        }
+        let str = core::str::from_utf8(bytes).map_err(FromBytesError::Utf8)?;
+        Ok(get_or_intern(str))
+    }
+
+    pub fn from_utf16(units: &[u16]) -> Result<StrRef, std::string::FromUtf16Error> {
+        Ok(put_heap(String::from_utf16(units)?))
+    }
+
+    // Unpaired surrogates become U+FFFD.
+    pub fn from_utf16_lossy(units: &[u16]) -> StrRef {
+        put_heap(String::from_utf16_lossy(units))
+    }
+
+    // Keeps the first `max` grapheme clusters and appends `ellipsis` when
+    // anything was cut off.
+    #[cfg(feature = "unicode")]
+    pub fn truncate_graphemes(&self, max: usize, ellipsis: &str) -> StrRef {
+        use unicode_segmentation::UnicodeSegmentation;
+        let str = self.deref();
+        match str.grapheme_indices(true).nth(max) {
+            None => self.clone(),
+            Some((end, _)) => {
+                let mut buf = String::with_capacity(end + ellipsis.len());
+                buf.push_str(&str[..end]);
+                buf.push_str(ellipsis);
+                put_heap(buf)
+            }
+        }
+    }
+
+    // Slices by grapheme cluster index, `None` if `range` is out of bounds or
+    // decreasing.
+    #[cfg(feature = "unicode")]
+    pub fn substr_graphemes(&self, range: core::ops::Range<usize>) -> Option<StrRef> {
+        use unicode_segmentation::UnicodeSegmentation;
+        let str = self.deref();
+        let mut bounds = str.grapheme_indices(true).map(|(i, _)| i).chain([str.len()]);
+        let start = bounds.nth(range.start)?;
+        let end = match range.end.checked_sub(range.start)? {
+            0 => start,
+            n => bounds.nth(n - 1)?,
+        };
+        Some(get_or_intern(&str[start..end]))
+    }
+
+    // Borrows static entries, copies heap ones.
+    pub fn to_cow(&self) -> Cow<'static, str> {
+        unsafe { global() }.get_cow(self.clone()).expect("null string ref").clone()
+    }
+
+    fn pad(&self, width: usize, fill: char, left: bool) -> StrRef {
+        let str = self.deref();
+        let len = str.chars().count();
+        if len >= width {
+            return self.clone();
+        }
+        let padding = core::iter::repeat_n(fill, width - len);
+        let mut buf = String::with_capacity(str.len() + (width - len) * fill.len_utf8());
+        if left {
+            buf.extend(padding);
+            buf.push_str(str);
+        } else {
+            buf.push_str(str);
+            buf.extend(padding);
+        }
+        put_heap(buf)
+    }
+
+    // Widths are counted in chars.
+    pub fn pad_left(&self, width: usize, fill: char) -> StrRef {
+        self.pad(width, fill, true)
+    }
+
+    pub fn pad_right(&self, width: usize, fill: char) -> StrRef {
+        self.pad(width, fill, false)
+    }
+
+    pub fn assert_eq(&self, expected: &str) -> Result<(), String> {
+        let found = self.deref();
+        if found == expected {
+            return Ok(());
+        }
+        let at = found
+            .bytes()
+            .zip(expected.bytes())
+            .position(|(a, b)| a != b)
+            .unwrap_or(found.len().min(expected.len()));
+        Err(format!("expected {:?}, found {:?} (first difference at byte {})", expected, found, at))
+    }
+
+    // Shows at most `max` bytes, cut at a char boundary and followed by `…`.
+    pub fn display_truncated(&self, max: usize) -> impl fmt::Display + '_ {
+        struct Truncated<'a>(&'a str, usize);
+
+        impl fmt::Display for Truncated<'_> {
+            fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let Truncated(str, max) = *self;
+                if str.len() <= max {
+                    return fmt.write_str(str);
+                }
+                let end = (0..=max).rev().find(|&i| str.is_char_boundary(i)).unwrap_or(0);
+                fmt.write_str(&str[..end])?;
+                fmt.write_str("…")
+            }
+        }
+
+        Truncated(self.deref(), max)
+    }
+
+    // Replaces `\0` with U+FFFD, other control chars can be replaced through
+    // `put_normalized` with `Normalizer::replace_control`.
+    pub fn from_sanitized(str: &str) -> StrRef {
+        normalize_cow(str, |str| {
+            if str.contains('\0') {
+                Cow::Owned(str.replace('\0', "\u{FFFD}"))
+            } else {
+                Cow::Borrowed(str)
+            }
+        })
+    }
+
+    pub fn in_pool<'a>(&self, pool: &'a StrPool) -> PoolStr<'a> {
+        PoolStr(pool.get(self.clone()).expect("null string ref"))
+    }
+
+    pub fn trimmed(&self) -> StrRef {
+        normalize_cow(self.deref(), |str| Cow::Borrowed(str.trim()))
+    }
+
+    pub fn to_ascii_lowercase_interned(&self) -> StrRef {
+        normalize_cow(self.deref(), |str| {
+            if str.bytes().any(|b| b.is_ascii_uppercase()) {
+                Cow::Owned(str.to_ascii_lowercase())
+            } else {
+                Cow::Borrowed(str)
+            }
+        })
+    }
+
+    // Renders as a quoted literal with the escapes of `str`'s `Debug`, e.g.
+    // for generated source.
+    pub fn escape_debug_display(&self) -> impl fmt::Display + '_ {
+        struct Escaped<'a>(&'a str);
+
+        impl fmt::Display for Escaped<'_> {
+            fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Debug::fmt(self.0, fmt)
+            }
+        }
+
+        Escaped(self.deref())
+    }
+
+    // Segments containing `sep` themselves do not survive the round trip, and
+    // neither does an empty list, which splits back into one empty segment.
+    pub fn from_segments(segs: &[&str], sep: char) -> StrRef {
+        let mut buf = String::with_capacity(segs.iter().map(|s| s.len() + sep.len_utf8()).sum());
+        for (i, seg) in segs.iter().enumerate() {
+            if i > 0 {
+                buf.push(sep);
+            }
+            buf.push_str(seg);
+        }
+        put_heap(buf)
+    }
+
+    pub fn segments(&self, sep: char) -> impl Iterator<Item = &str> {
+        self.deref().split(sep)
+    }
+
+    // Copies only on a miss, the buffer is never retained by the pool.
+    #[cfg(feature = "bytes")]
+    pub fn try_from_bytes(bytes: bytes::Bytes) -> Result<StrRef, core::str::Utf8Error> {
+        Ok(get_or_intern(core::str::from_utf8(&bytes)?))
+    }
+
+    pub fn eq_ignore_ascii_case(&self, other: &str) -> bool {
+        self.deref().eq_ignore_ascii_case(other)
+    }
+
+    pub fn cmp_ignore_ascii_case(&self, other: &str) -> std::cmp::Ordering {
+        self.deref()
+            .bytes()
+            .map(|b| b.to_ascii_lowercase())
+            .cmp(other.bytes().map(|b| b.to_ascii_lowercase()))
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+unsafe fn global<'a>() -> &'a mut StrPool {
+    assert!(!IN_HOOK.get(), "global pool used from an insert hook");
+    &mut *GLOBAL_POOL.with(|r| r.get())
+}
+
+// While the global pool's insert hook runs, the pool is mutably borrowed by
+// the insert, so any use of the global pool from inside the hook panics
+// rather than alias that borrow or recurse into the hook.
+#[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+fn run_hook<R>(f: impl FnOnce() -> R) -> R {
+    struct Restore(bool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            IN_HOOK.set(self.0);
+        }
+    }
+
+    let _restore = Restore(IN_HOOK.replace(true));
+    f()
+}
+
+#[cfg(all(target_arch = "wasm32", not(target_feature = "atomics")))]
+use wasm::{global, run_hook};
+
+// The global pool lives in a `thread_local!`, which every target with `std`
+// provides. It is gone only while the thread is being torn down, e.g. in
+// destructors of other thread locals: there the free functions and `Deref`
+// panic, while this returns `None` without running `f`.
+// On single-threaded wasm32 the pool is a static and always there.
+// `f` only gets the `Global` handle, which interns and reads but never hands
+// out the pool itself: anything that drops entries would free buffers derefed
+// slices still point into.
+pub fn try_global<R>(f: impl FnOnce(Global) -> R) -> Option<R> {
+    #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+    GLOBAL_POOL.try_with(|_| ()).ok()?;
+    Some(f(Global))
+}
+
+// For normalizations that borrow their input when it is already normalized.
+fn normalize_cow<'a>(str: &'a str, f: impl FnOnce(&'a str) -> Cow<'a, str>) -> StrRef {
+    unsafe { global() }.put_cow(f(str))
+}
+
+pub fn put_static(str: &'static str) -> StrRef {
+    unsafe { global() }.put_static(str)
+}
+
+pub fn put_heap(str: String) -> StrRef {
+    unsafe { global() }.put_heap(str)
+}
+
+// The hook must not use the global pool itself, doing so panics.
+pub fn set_global_on_insert(mut on_insert: OnInsert) {
+    let guarded = move |r: StrRef, str: &str| run_hook(|| on_insert(r, str));
+    unsafe { global() }.set_on_insert(Box::new(guarded))
 }
 
-impl Default for StrPool {
-    fn default() -> Self {
-        Self { pool: Default::default() }
+pub fn set_global_empty_policy(policy: EmptyPolicy) {
+    unsafe { global() }.set_empty_policy(policy)
+}
+
+pub fn freeze_global() {
+    unsafe { global() }.freeze()
+}
+
+// The entry is leaked to keep the slice valid, it becomes a static entry and
+// survives `reset_heap_only`.
+pub fn intern_with_str(str: String) -> (StrRef, &'static str) {
+    let pool = unsafe { global() };
+    let r = pool.put_heap(str);
+    let str = pool.leak(r.ptr).expect("null string ref");
+    (r, str)
+}
+
+// Derefs of refs to the dropped entries may still be around, so their buffers
+// are leaked as by `StrRef::leak` instead of freed.
+pub fn reset_heap_only() -> Remap {
+    let pool = unsafe { global() };
+    let heap: Vec<bool> = pool.pool.iter().map(|e| matches!(e.str, Cow::Owned(_))).collect();
+    for ptr in (0..heap.len()).filter(|&ptr| heap[ptr]) {
+        pool.leak(ptr);
     }
+    pool.retain_indices(|ptr, _| !heap[ptr])
 }
 
-thread_local! {
-    static GLOBAL_POOL: UnsafeCell<StrPool> = Default::default();
+pub fn get_or_intern(str: &str) -> StrRef {
+    unsafe { global() }.get_or_intern(str)
 }
 
-#[derive(Clone)]
-pub struct StrRef {
-    ptr: usize,
+pub fn put_fmt(args: fmt::Arguments) -> StrRef {
+    match args.as_str() {
+        Some(str) => put_static(str),
+        None => put_heap(fmt::format(args)),
+    }
 }
 
-impl StrPool {
-    pub fn put_static(&mut self, str: &'static str) -> StrRef {
-        let (ptr, _) = self.pool.insert_full(Cow::Borrowed(str));
-        // println!("put_static: '{}' -> {} new={}", str, ptr, new);
-        StrRef { ptr }
+// Bytes that are only checked for UTF-8 and interned into the global pool when
+// first resolved. Like `FmtCache`, the cached ref is checked against the pool
+// on later calls.
+pub struct DeferredRef {
+    bytes: &'static [u8],
+    cached: RefCell<Option<StrRef>>,
+}
+
+impl DeferredRef {
+    pub const fn new(bytes: &'static [u8]) -> DeferredRef {
+        DeferredRef { bytes, cached: RefCell::new(None) }
     }
 
-    pub fn put_heap(&mut self, str: String) -> StrRef {
-        // print!("put_heap: '{}'", str);
-        let (ptr, _) = self.pool.insert_full(Cow::Owned(str));
-        // println!(" -> {} new={}", ptr, new);
-        StrRef { ptr }
+    pub fn bytes(&self) -> &'static [u8] {
+        self.bytes
     }
 
-    pub fn get(&self, r: StrRef) -> Option<&str> {
-        let s = self.pool.get_index(r.ptr).map(AsRef::as_ref);
-        // println!("get: {} -> {:?}", r.ptr, s);
-        s
+    pub fn resolve(&self) -> Result<StrRef, core::str::Utf8Error> {
+        let mut cached = self.cached.borrow_mut();
+        if let Some(r) = &*cached {
+            if unsafe { global() }.get(r.clone()).map(str::as_bytes) == Some(self.bytes) {
+                return Ok(r.clone());
+            }
+        }
+        let r = put_static(core::str::from_utf8(self.bytes)?);
+        *cached = Some(r.clone());
+        Ok(r)
     }
 }
 
-impl Deref for StrRef {
+// Backs `intern_fmt!`, one instance per call site.
+#[doc(hidden)]
+pub struct FmtCache {
+    buf: RefCell<String>,
+    last: RefCell<Option<StrRef>>,
+}
+
+impl FmtCache {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self { buf: RefCell::new(String::new()), last: RefCell::new(None) }
+    }
+
+    // The cached ref is checked by index against the pool, which also catches
+    // refs invalidated by a reset.
+    pub fn intern(&self, args: fmt::Arguments) -> StrRef {
+        use fmt::Write;
+        let mut buf = self.buf.borrow_mut();
+        buf.clear();
+        buf.write_fmt(args).expect("a formatting trait implementation returned an error");
+        let mut last = self.last.borrow_mut();
+        if let Some(r) = &*last {
+            if unsafe { global() }.get(r.clone()) == Some(buf.as_str()) {
+                return r.clone();
+            }
+        }
+        let r = get_or_intern(&buf);
+        *last = Some(r.clone());
+        r
+    }
+}
+
+// Formats and interns into the global pool. Each call site remembers its last
+// result, so formatting the same output again skips the pool lookup.
+#[macro_export]
+macro_rules! intern_fmt {
+    ($($arg:tt)*) => {{
+        ::std::thread_local! {
+            static CACHE: $crate::FmtCache = const { $crate::FmtCache::new() };
+        }
+        CACHE.with(|cache| cache.intern(::core::format_args!($($arg)*)))
+    }};
+}
+
+// Formats and interns into the given `Interner`, e.g. a `StrPool` or `Global`:
+// `intern_write!(pool, "{}-{}", a, b)`.
+#[macro_export]
+macro_rules! intern_write {
+    ($pool:expr, $($arg:tt)*) => {{
+        use $crate::Interner as _;
+        $pool.intern_fmt(::core::format_args!($($arg)*))
+    }};
+}
+
+// Generates a fieldless enum for a fixed set of tokens, so they can be matched
+// as integers, e.g. `interned_enum! { Keyword { If = "if", Else = "else" } }`.
+// A variant's discriminant is its position in the list, and `str_ref` interns
+// it into the global pool as a static string on first use. The ref is cached
+// per thread and variant, later calls only check it against the pool.
+#[macro_export]
+macro_rules! interned_enum {
+    ($(#[$meta:meta])* $vis:vis $name:ident { $($variant:ident = $str:literal),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        $vis enum $name {
+            $($variant),*
+        }
+
+        impl $name {
+            pub const ALL: &'static [$name] = &[$($name::$variant),*];
+
+            pub const fn index(self) -> usize {
+                self as usize
+            }
+
+            pub const fn as_str(self) -> &'static str {
+                match self {
+                    $($name::$variant => $str),*
+                }
+            }
+
+            pub fn str_ref(self) -> $crate::StrRef {
+                ::std::thread_local! {
+                    static REFS: [$crate::DeferredRef; $name::ALL.len()] = const {
+                        [$($crate::DeferredRef::new($str.as_bytes())),*]
+                    };
+                }
+                REFS.with(|refs| refs[self as usize].resolve().expect("variant is a str literal"))
+            }
+
+            #[allow(clippy::should_implement_trait)]
+            pub fn from_str(str: &str) -> ::core::option::Option<$name> {
+                match str {
+                    $($str => ::core::option::Option::Some($name::$variant),)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+
+            pub fn from_str_ref(r: &$crate::StrRef) -> ::core::option::Option<$name> {
+                $name::from_str(r)
+            }
+        }
+    };
+}
+
+// Runs `f` against a fresh global pool and puts the previous one back
+// afterwards, even if `f` panics. Refs created inside must not escape the
+// scope: outside, they resolve to whatever entry the outer pool has at their
+// index, or to nothing, and this is not checked. Slices derefed inside may
+// outlive the scope, so the scoped pool's heap buffers are leaked rather than
+// freed with it.
+pub fn with_scoped_pool<R>(f: impl FnOnce() -> R) -> R {
+    struct Restore(StrPool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            core::mem::swap(unsafe { global() }, &mut self.0);
+            for ptr in 0..self.0.pool.len() {
+                self.0.leak(ptr);
+            }
+        }
+    }
+
+    let _restore = Restore(core::mem::take(unsafe { global() }));
+    f()
+}
+
+pub fn reserve_global(additional: usize) {
+    unsafe { global() }.reserve(additional);
+}
+
+// Gives back the capacity the global pool has beyond its entries, e.g. once a
+// program is done loading and only looks strings up from then on.
+pub fn shrink_global() {
+    unsafe { global() }.shrink_to_fit();
+}
+
+// Sizes the global pool up front for a batch of inserts, e.g. from a rough
+// estimate of the strings in a document. The capacity is kept after the
+// guard goes away, shrinking it would only make the next batch grow again,
+// `shrink_global` releases it when no batch follows:
+//
+//     let _reserve = GlobalReserveGuard::new(src.len() / 16);
+//     let doc = parse(&src);
+pub struct GlobalReserveGuard {
+    _not_send: PhantomData<*const ()>,
+}
+
+impl GlobalReserveGuard {
+    pub fn new(additional: usize) -> GlobalReserveGuard {
+        reserve_global(additional);
+        GlobalReserveGuard { _not_send: PhantomData }
+    }
+}
+
+pub fn intern_map(map: HashMap<String, String>) -> HashMap<StrRef, StrRef> {
+    let pool = unsafe { global() };
+    pool.reserve(map.len() * 2);
+    let mut interned = HashMap::with_capacity(map.len());
+    for (k, v) in map {
+        interned.insert(pool.put_heap(k), pool.put_heap(v));
+    }
+    interned
+}
+
+// Borrowed items are stored as static entries, owned ones without copying.
+pub fn intern_cows<I: IntoIterator<Item = Cow<'static, str>>>(iter: I) -> Vec<StrRef> {
+    let pool = unsafe { global() };
+    iter.into_iter()
+        .map(|str| match str {
+            Cow::Borrowed(str) => pool.put_static(str),
+            Cow::Owned(str) => pool.put_heap(str),
+        })
+        .collect()
+}
+
+// One ref per component as `Path::components` yields them, the root as `/`.
+// Invalid UTF-8 in a component is replaced with U+FFFD.
+pub fn intern_path_components(path: &std::path::Path) -> Vec<StrRef> {
+    let components = path.components().map(|c| c.as_os_str().to_string_lossy());
+    components.map(|c| unsafe { global() }.put_cow(c)).collect()
+}
+
+// Like `intern_path_components`, but `None` if any component is not UTF-8,
+// in which case nothing is interned.
+pub fn try_intern_path_components(path: &std::path::Path) -> Option<Vec<StrRef>> {
+    let components: Option<Vec<&str>> = path.components().map(|c| c.as_os_str().to_str()).collect();
+    Some(components?.into_iter().map(get_or_intern).collect())
+}
+
+// The first few parts are held on the stack to size the buffer exactly, which
+// covers most paths, the buffer only grows for the parts after them.
+pub fn intern_concat<'a, I: IntoIterator<Item = &'a str>>(parts: I) -> StrRef {
+    let mut parts = parts.into_iter();
+    let mut head = [""; 8];
+    for (slot, part) in head.iter_mut().zip(parts.by_ref()) {
+        *slot = part;
+    }
+    let mut buf = String::with_capacity(head.iter().map(|part| part.len()).sum());
+    head.iter().for_each(|part| buf.push_str(part));
+    parts.for_each(|part| buf.push_str(part));
+    put_heap(buf)
+}
+
+// The entry is removed when the last guard for it is dropped, unless it was
+// interned before, or got handed out by a regular `put_*` call meanwhile. Other
+// entries keep their index, refs to the removed one no longer resolve.
+pub struct ScopedEntry {
+    r: StrRef,
+    generation: u64,
+    _not_send: PhantomData<*const ()>,
+}
+
+pub fn intern_scoped(str: &str) -> ScopedEntry {
+    let pool = unsafe { global() };
+    let ptr = pool.intern_scoped(str);
+    ScopedEntry { r: StrRef::new(ptr), generation: pool.generation.0, _not_send: PhantomData }
+}
+
+impl ScopedEntry {
+    pub fn str_ref(&self) -> &StrRef {
+        &self.r
+    }
+}
+
+impl Deref for ScopedEntry {
     type Target = str;
 
-    fn deref(&self) -> &Self::Target {
-        unsafe { global() }.get(self.clone()).expect("null string ref")
+    fn deref(&self) -> &str {
+        &self.r
     }
 }
 
-unsafe fn global<'a>() -> &'a mut StrPool {
-    &mut *GLOBAL_POOL.with(|r| r.get())
+impl Drop for ScopedEntry {
+    fn drop(&mut self) {
+        // After a compaction or reset the index may name another entry.
+        let pool = unsafe { global() };
+        if pool.generation.0 == self.generation {
+            pool.release_scoped(self.r.ptr);
+        }
+    }
 }
 
-pub fn put_static(str: &'static str) -> StrRef {
-    unsafe { global() }.put_static(str)
+// Collapses runs of refs to the same entry, without resolving any of them.
+pub fn dedup_runs(refs: &mut Vec<StrRef>) {
+    refs.dedup_by(|a, b| a.same_index(b));
 }
 
-pub fn put_heap(str: String) -> StrRef {
-    unsafe { global() }.put_heap(str)
+pub fn join_to_string(parts: &[StrRef], sep: &str) -> String {
+    let pool = unsafe { global() };
+    let parts: Vec<&str> =
+        parts.iter().map(|r| pool.get(r.clone()).expect("null string ref")).collect();
+    let len = parts.iter().map(|s| s.len()).sum::<usize>()
+        + sep.len() * parts.len().saturating_sub(1);
+    let mut buf = String::with_capacity(len);
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            buf.push_str(sep);
+        }
+        buf.push_str(part);
+    }
+    buf
+}
+
+// A ref resolved against a local pool, with the conveniences `StrRef` only
+// has for the global one.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PoolStr<'a>(&'a str);
+
+impl Deref for PoolStr<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl fmt::Debug for PoolStr<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+
+impl fmt::Display for PoolStr<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+
+// The global pool is thread-local, so there is no lock to hold; the guard
+// looks the pool up once and is kept on the current thread.
+pub struct PoolReadGuard {
+    pool: &'static StrPool,
+    _not_send: PhantomData<*const ()>,
+}
+
+pub fn read_guard() -> PoolReadGuard {
+    PoolReadGuard { pool: unsafe { global() }, _not_send: PhantomData }
+}
+
+impl PoolReadGuard {
+    pub fn resolve(&self, r: StrRef) -> &str {
+        self.pool.get(r).expect("null string ref")
+    }
+}
+
+pub trait Interner {
+    fn intern(&mut self, str: &str) -> StrRef;
+
+    fn resolve(&self, r: StrRef) -> Option<&str>;
+
+    // Backs `intern_write!`.
+    fn intern_fmt(&mut self, args: fmt::Arguments) -> StrRef {
+        match args.as_str() {
+            Some(str) => self.intern(str),
+            None => self.intern(&fmt::format(args)),
+        }
+    }
+}
+
+// `Interner` must stay object safe, plugins take `&mut dyn Interner`.
+const _: Option<&dyn Interner> = None;
+
+impl<I: Interner + ?Sized> Interner for &mut I {
+    fn intern(&mut self, str: &str) -> StrRef {
+        (**self).intern(str)
+    }
+
+    fn resolve(&self, r: StrRef) -> Option<&str> {
+        (**self).resolve(r)
+    }
+}
+
+impl Interner for StrPool {
+    fn intern(&mut self, str: &str) -> StrRef {
+        self.get_or_intern(str)
+    }
+
+    fn resolve(&self, r: StrRef) -> Option<&str> {
+        self.get(r)
+    }
+}
+
+// Stands for the thread-local global pool.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Global;
+
+// Read-only views of the global pool's counters.
+impl Global {
+    pub fn len(self) -> usize {
+        unsafe { global() }.len()
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(self) -> usize {
+        unsafe { global() }.capacity()
+    }
+
+    pub fn savings(self) -> (u64, u64) {
+        unsafe { global() }.savings()
+    }
+}
+
+impl Interner for Global {
+    fn intern(&mut self, str: &str) -> StrRef {
+        get_or_intern(str)
+    }
+
+    fn resolve(&self, r: StrRef) -> Option<&str> {
+        unsafe { global() }.get(r)
+    }
+}
+
+pub trait OptStrRefExt {
+    fn resolve_or_empty(&self) -> &str;
+}
+
+impl OptStrRefExt for Option<StrRef> {
+    fn resolve_or_empty(&self) -> &str {
+        self.as_deref().unwrap_or("")
+    }
+}
+
+// Hashes the content with 64-bit FNV-1a, which has no per-process seed, so
+// equal strings get the same `stable_hash` on every machine, e.g. to pick a
+// shard. `Hash` feeds that value to the hasher.
+#[derive(Clone, PartialEq, Eq)]
+pub struct StableHash(pub StrRef);
+
+impl StableHash {
+    pub fn stable_hash(&self) -> u64 {
+        self.0.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    }
+}
+
+impl core::hash::Hash for StableHash {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        state.write_u64(self.stable_hash());
+    }
 }
 
 impl Default for StrRef {
@@ -67,9 +1900,14 @@ impl Default for StrRef {
     }
 }
 
+// A ref left dangling by a reset only equals a ref to the same index.
 impl PartialEq<Self> for StrRef {
     fn eq(&self, other: &Self) -> bool {
-        self.deref() == other.deref()
+        match (self.try_resolve(), other.try_resolve()) {
+            (Some(a), Some(b)) => a == b,
+            (None, None) => self.ptr == other.ptr,
+            _ => false,
+        }
     }
 }
 
@@ -77,7 +1915,7 @@ impl Eq for StrRef {}
 
 impl PartialOrd<Self> for StrRef {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.deref().partial_cmp(other.deref())
+        Some(self.cmp(other))
     }
 }
 
@@ -93,6 +1931,18 @@ impl PartialEq<str> for StrRef {
     }
 }
 
+impl PartialEq<[u8]> for StrRef {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_bytes() == other
+    }
+}
+
+impl PartialEq<&[u8]> for StrRef {
+    fn eq(&self, other: &&[u8]) -> bool {
+        self.as_bytes() == *other
+    }
+}
+
 // impl<T: AsRef<str>> PartialEq<T> for StrRef {
 //     fn eq(&self, other: &T) -> bool {
 //         self == other
@@ -143,10 +1993,75 @@ impl From<&str> for StrRef {
     }
 }
 
+impl From<&mut str> for StrRef {
+    #[inline]
+    fn from(value: &mut str) -> Self {
+        StrRef::from(&*value)
+    }
+}
+
+impl From<&String> for StrRef {
+    #[inline]
+    fn from(value: &String) -> Self {
+        StrRef::from(value.as_str())
+    }
+}
+
 impl From<Box<str>> for StrRef {
     #[inline]
     fn from(value: Box<str>) -> Self {
-        put_heap(String::from(value))
+        unsafe { global() }.put_boxed(value)
+    }
+}
+
+// One bound for generic code that interns keys of mixed types. Covers every
+// infallible conversion into `StrRef`, integers and chars are interned in their
+// `Display` form. Byte input has to go through `TryFrom` instead.
+pub trait IntoStrRef {
+    fn into_str_ref(self) -> StrRef;
+}
+
+impl<T: Into<StrRef>> IntoStrRef for T {
+    fn into_str_ref(self) -> StrRef {
+        self.into()
+    }
+}
+
+// Room for any integer up to `i128::MIN`.
+struct IntBuf {
+    buf: [u8; 40],
+    len: usize,
+}
+
+impl fmt::Write for IntBuf {
+    fn write_str(&mut self, str: &str) -> fmt::Result {
+        let end = self.len + str.len();
+        self.buf.get_mut(self.len..end).ok_or(fmt::Error)?.copy_from_slice(str.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+// Integers are formatted on the stack and looked up by that, so only ones not
+// interned yet allocate.
+macro_rules! impl_into_str_ref_for_int {
+    ($($int:ty),*) => {$(
+        impl IntoStrRef for $int {
+            fn into_str_ref(self) -> StrRef {
+                use fmt::Write;
+                let mut buf = IntBuf { buf: [0; 40], len: 0 };
+                write!(buf, "{}", self).unwrap();
+                get_or_intern(unsafe { core::str::from_utf8_unchecked(&buf.buf[..buf.len]) })
+            }
+        }
+    )*};
+}
+
+impl_into_str_ref_for_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl IntoStrRef for char {
+    fn into_str_ref(self) -> StrRef {
+        get_or_intern(self.encode_utf8(&mut [0; 4]))
     }
 }
 
@@ -157,12 +2072,47 @@ impl From<StrRef> for String {
     }
 }
 
+impl From<StrRef> for Vec<u8> {
+    #[inline]
+    fn from(value: StrRef) -> Self {
+        value.as_bytes().to_vec()
+    }
+}
+
+impl From<StrRef> for Cow<'static, str> {
+    #[inline]
+    fn from(value: StrRef) -> Self {
+        value.to_cow()
+    }
+}
+
+// Inline strings are looked up in place, heap ones hand their buffer over.
+#[cfg(feature = "compact_str")]
+impl From<compact_str::CompactString> for StrRef {
+    #[inline]
+    fn from(value: compact_str::CompactString) -> Self {
+        if value.is_heap_allocated() {
+            put_heap(value.into_string())
+        } else {
+            get_or_intern(&value)
+        }
+    }
+}
+
+#[cfg(feature = "compact_str")]
+impl From<StrRef> for compact_str::CompactString {
+    #[inline]
+    fn from(value: StrRef) -> Self {
+        compact_str::CompactString::new(value.deref())
+    }
+}
+
 impl TryFrom<&[u8]> for StrRef {
     type Error = core::str::Utf8Error;
 
     #[inline]
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        Ok(put_heap(core::str::from_utf8(value)?.to_owned()))
+        Ok(get_or_intern(core::str::from_utf8(value)?))
     }
 }
 
@@ -207,13 +2157,14 @@ impl core::fmt::Display for StrRef {
 }
 
 #[cfg(feature = "serde")]
-mod serde {
+pub mod serde {
+    use core::fmt;
     use serde::{
-        de::{Deserialize, Deserializer},
-        ser::{Serialize, Serializer},
+        de::{self, Deserialize, DeserializeSeed, Deserializer, SeqAccess, Visitor},
+        ser::{Serialize, SerializeTuple, Serializer},
     };
 
-    use super::StrRef;
+    use super::{StrPool, StrRef};
 
     impl Serialize for StrRef {
         #[inline]
@@ -231,7 +2182,131 @@ mod serde {
         where
             D: Deserializer<'de>,
         {
-            String::deserialize(deserializer).map(StrRef::from)
+            StrRefSeed(unsafe { crate::global() }).deserialize(deserializer)
+        }
+    }
+
+    // Deserializes into the given pool instead of the global one.
+    pub struct StrRefSeed<'a>(pub &'a mut StrPool);
+
+    impl<'de> DeserializeSeed<'de> for StrRefSeed<'_> {
+        type Value = StrRef;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<StrRef, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_str(StrRefVisitor(self.0))
+        }
+    }
+
+    // Strings handed out by reference are only copied when they are not
+    // interned yet.
+    struct StrRefVisitor<'a>(&'a mut StrPool);
+
+    impl<'de> Visitor<'de> for StrRefVisitor<'_> {
+        type Value = StrRef;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a string")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<StrRef, E> {
+            Ok(self.0.get_or_intern(v))
+        }
+
+        fn visit_string<E: de::Error>(self, v: String) -> Result<StrRef, E> {
+            Ok(self.0.put_heap(v))
+        }
+
+        // For formats that encode strings as raw bytes.
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<StrRef, E> {
+            match core::str::from_utf8(v) {
+                Ok(v) => Ok(self.0.get_or_intern(v)),
+                Err(_) => Err(E::invalid_value(de::Unexpected::Bytes(v), &self)),
+            }
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<StrRef, E> {
+            match String::from_utf8(v) {
+                Ok(v) => Ok(self.0.put_heap(v)),
+                Err(err) => Err(E::invalid_value(de::Unexpected::Bytes(err.as_bytes()), &self)),
+            }
+        }
+    }
+
+    // For `#[serde(deserialize_with = "strpool::serde::deserialize_borrowed")]`:
+    // requires the deserializer to lend out the string (e.g. JSON without
+    // escapes) and fails otherwise, so no intermediate `String` is ever built.
+    pub fn deserialize_borrowed<'de, D>(deserializer: D) -> Result<StrRef, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        <&'de str>::deserialize(deserializer).map(crate::get_or_intern)
+    }
+
+    // Serializes a list of refs as `(dict, indices)`, where `dict` holds each
+    // distinct string once in sorted order and `indices` point into it. The
+    // output only depends on the logical contents, not on interning order.
+    pub struct SortedDict<T>(pub T);
+
+    impl<T: AsRef<[StrRef]>> Serialize for SortedDict<T> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let refs = self.0.as_ref();
+            let mut dict: Vec<&str> = refs.iter().map(AsRef::as_ref).collect();
+            dict.sort_unstable();
+            dict.dedup();
+            let indices: Vec<usize> = refs
+                .iter()
+                .map(|r| dict.binary_search(&r.as_ref()).expect("missing dict entry"))
+                .collect();
+            let mut tuple = serializer.serialize_tuple(2)?;
+            tuple.serialize_element(&dict)?;
+            tuple.serialize_element(&indices)?;
+            tuple.end()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for SortedDict<Vec<StrRef>> {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct SortedDictVisitor;
+
+            impl<'de> Visitor<'de> for SortedDictVisitor {
+                type Value = SortedDict<Vec<StrRef>>;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("a (dict, indices) pair")
+                }
+
+                fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                where
+                    A: SeqAccess<'de>,
+                {
+                    let dict: Vec<StrRef> = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                    let indices: Vec<usize> = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                    indices
+                        .into_iter()
+                        .map(|i| {
+                            dict.get(i).cloned().ok_or_else(|| {
+                                de::Error::custom(format_args!("dict index {} out of range", i))
+                            })
+                        })
+                        .collect::<Result<_, _>>()
+                        .map(SortedDict)
+                }
+            }
+
+            deserializer.deserialize_tuple(2, SortedDictVisitor)
         }
     }
 }
@@ -0,0 +1,53 @@
+use std::ops::Range;
+
+use crate::{StrPool, StrRef, TryPutError};
+
+// Issues refs offset into the band `base..base + band`, so refs of different
+// pools can be told apart by index alone. Bands are not coordinated between
+// pools: giving every pool its own `base`, at least `band` apart, e.g.
+// `plugin_id * band`, is what keeps them from overlapping.
+pub struct NamespacedPool {
+    base: usize,
+    pool: StrPool,
+}
+
+impl NamespacedPool {
+    pub fn new(base: usize, band: usize) -> NamespacedPool {
+        base.checked_add(band).expect("index band out of range");
+        let mut pool = StrPool::default();
+        pool.set_max_entries(Some(band));
+        NamespacedPool { base, pool }
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        let band = self.pool.max_entries().unwrap();
+        self.base..self.base + band
+    }
+
+    pub fn contains(&self, r: &StrRef) -> bool {
+        self.range().contains(&r.ptr)
+    }
+
+    pub fn intern(&mut self, str: &str) -> Result<StrRef, TryPutError> {
+        let StrRef { ptr, .. } = match self.pool.hit(str) {
+            Some(r) => r,
+            None => self.pool.try_put_heap(str.to_owned())?,
+        };
+        Ok(StrRef::new(self.base + ptr))
+    }
+
+    pub fn get(&self, r: StrRef) -> Option<&str> {
+        if !self.contains(&r) {
+            return None;
+        }
+        self.pool.get(StrRef::new(r.ptr - self.base))
+    }
+
+    pub fn len(&self) -> usize {
+        self.pool.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pool.is_empty()
+    }
+}
@@ -0,0 +1,125 @@
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Trim,
+    Lowercase,
+    CollapseWhitespace,
+    // Replaces `\0`, or every control char if `all` is set.
+    ReplaceControl { placeholder: char, all: bool },
+    #[cfg(feature = "unicode")]
+    Nfc,
+}
+
+// Steps run in the order they were added. Each step borrows its input when it
+// has nothing to change, so already-normalized strings never allocate.
+#[derive(Debug, Clone, Default)]
+pub struct Normalizer {
+    steps: Vec<Step>,
+}
+
+impl Normalizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn trim(mut self) -> Self {
+        self.steps.push(Step::Trim);
+        self
+    }
+
+    pub fn lowercase(mut self) -> Self {
+        self.steps.push(Step::Lowercase);
+        self
+    }
+
+    pub fn collapse_whitespace(mut self) -> Self {
+        self.steps.push(Step::CollapseWhitespace);
+        self
+    }
+
+    pub fn replace_nul(mut self, placeholder: char) -> Self {
+        self.steps.push(Step::ReplaceControl { placeholder, all: false });
+        self
+    }
+
+    pub fn replace_control(mut self, placeholder: char) -> Self {
+        self.steps.push(Step::ReplaceControl { placeholder, all: true });
+        self
+    }
+
+    #[cfg(feature = "unicode")]
+    pub fn nfc(mut self) -> Self {
+        self.steps.push(Step::Nfc);
+        self
+    }
+
+    pub fn apply<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        self.steps.iter().fold(Cow::Borrowed(s), |s, step| step.run(s))
+    }
+}
+
+impl Step {
+    fn run<'a>(self, s: Cow<'a, str>) -> Cow<'a, str> {
+        let changed = match self {
+            Step::Trim => {
+                let trimmed = s.trim();
+                if trimmed.len() == s.len() {
+                    return s;
+                }
+                if let Cow::Borrowed(s) = s {
+                    return Cow::Borrowed(s.trim());
+                }
+                trimmed.to_owned()
+            }
+            Step::Lowercase => {
+                if s.chars().all(|c| c.to_lowercase().eq([c])) {
+                    return s;
+                }
+                s.to_lowercase()
+            }
+            Step::CollapseWhitespace => {
+                let mut prev_ws = false;
+                let collapsed = s.chars().all(|c| {
+                    let ws = c.is_whitespace();
+                    let ok = !ws || (c == ' ' && !prev_ws);
+                    prev_ws = ws;
+                    ok
+                });
+                if collapsed {
+                    return s;
+                }
+                let mut buf = String::with_capacity(s.len());
+                let mut prev_ws = false;
+                for c in s.chars() {
+                    if c.is_whitespace() {
+                        if !prev_ws {
+                            buf.push(' ');
+                        }
+                        prev_ws = true;
+                    } else {
+                        buf.push(c);
+                        prev_ws = false;
+                    }
+                }
+                buf
+            }
+            Step::ReplaceControl { placeholder, all } => {
+                let hit = |c: char| if all { c.is_control() } else { c == '\0' };
+                if !s.contains(hit) {
+                    return s;
+                }
+                s.chars().map(|c| if hit(c) { placeholder } else { c }).collect()
+            }
+            #[cfg(feature = "unicode")]
+            Step::Nfc => {
+                use unicode_normalization::UnicodeNormalization;
+                if unicode_normalization::is_nfc(&s) {
+                    return s;
+                }
+                s.nfc().collect()
+            }
+        };
+        Cow::Owned(changed)
+    }
+}
@@ -0,0 +1,136 @@
+use crate::StrRef;
+
+const EMPTY: u32 = u32::MAX;
+
+// Seeded FNV-1a with a final mix, the same at build time and at runtime.
+fn hash(seed: u32, str: &str) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325 ^ (seed as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    for byte in str.bytes() {
+        hash = (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^ (hash >> 33)
+}
+
+// A fixed set of keywords with a perfect hash computed at build time by
+// `codegen::generate`, so a lookup hashes twice and compares once. Keyword
+// `i` resolves from the ref with index `i`.
+pub struct PerfectPool {
+    keys: &'static [&'static str],
+    // Seed for the second hash of each bucket.
+    displacements: &'static [u32],
+    // Index into `keys`, or `EMPTY`.
+    slots: &'static [u32],
+}
+
+impl PerfectPool {
+    // Only meant to be called by generated code.
+    #[doc(hidden)]
+    pub const fn from_tables(
+        keys: &'static [&'static str],
+        displacements: &'static [u32],
+        slots: &'static [u32],
+    ) -> PerfectPool {
+        PerfectPool { keys, displacements, slots }
+    }
+
+    pub fn resolve_str(&self, str: &str) -> Option<StrRef> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        let bucket = hash(0, str) % self.displacements.len() as u64;
+        let seed = self.displacements[bucket as usize];
+        let slot = self.slots[(hash(seed, str) % self.slots.len() as u64) as usize];
+        if slot == EMPTY || self.keys[slot as usize] != str {
+            return None;
+        }
+        Some(StrRef::new(slot as usize))
+    }
+
+    pub fn get(&self, r: StrRef) -> Option<&'static str> {
+        self.keys.get(r.ptr).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+// For build scripts. `generate` returns a Rust expression of type
+// `strpool::PerfectPool`, meant to be written to `OUT_DIR` and pulled in with
+//
+//     static KEYWORDS: strpool::PerfectPool =
+//         include!(concat!(env!("OUT_DIR"), "/keywords.rs"));
+//
+// Repeated keywords are dropped, so keyword `i` of the deduplicated list gets
+// index `i`.
+pub mod codegen {
+    use std::{collections::HashSet, fmt::Write};
+
+    use super::{hash, EMPTY};
+
+    pub fn generate(keywords: &[&str]) -> String {
+        let mut seen = HashSet::new();
+        let keys: Vec<&str> = keywords.iter().copied().filter(|k| seen.insert(*k)).collect();
+        let (displacements, slots) = build(&keys);
+
+        let mut out = String::from("::strpool::PerfectPool::from_tables(&[");
+        for key in &keys {
+            write!(out, "{:?}, ", key).unwrap();
+        }
+        out.push_str("], &[");
+        for seed in &displacements {
+            write!(out, "{}, ", seed).unwrap();
+        }
+        out.push_str("], &[");
+        for slot in &slots {
+            write!(out, "{}, ", slot).unwrap();
+        }
+        out.push_str("])\n");
+        out
+    }
+
+    // Hash and displace: keys are split into buckets by one hash, then the
+    // largest buckets first each search for a seed that places all of their
+    // keys in free slots.
+    fn build(keys: &[&str]) -> (Vec<u32>, Vec<u32>) {
+        if keys.is_empty() {
+            return (Vec::new(), Vec::new());
+        }
+        let bucket_count = keys.len().div_ceil(4);
+        let slot_count = keys.len() + keys.len() / 4 + 1;
+        let mut buckets = vec![Vec::new(); bucket_count];
+        for (i, key) in keys.iter().enumerate() {
+            buckets[(hash(0, key) % bucket_count as u64) as usize].push(i);
+        }
+        let mut order: Vec<usize> = (0..bucket_count).collect();
+        order.sort_by_key(|&bucket| core::cmp::Reverse(buckets[bucket].len()));
+
+        let mut displacements = vec![0; bucket_count];
+        let mut slots = vec![EMPTY; slot_count];
+        let mut placed = Vec::new();
+        for bucket in order {
+            'seeds: for seed in 1.. {
+                placed.clear();
+                for &i in &buckets[bucket] {
+                    let slot = (hash(seed, keys[i]) % slot_count as u64) as usize;
+                    if slots[slot] != EMPTY || placed.contains(&slot) {
+                        continue 'seeds;
+                    }
+                    placed.push(slot);
+                }
+                for (&i, &slot) in buckets[bucket].iter().zip(&placed) {
+                    slots[slot] = i as u32;
+                }
+                displacements[bucket] = seed;
+                break;
+            }
+        }
+        (displacements, slots)
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    borrow::Cow,
+    io::{self, Read, Write},
+};
+
+use crate::{Entry, StrPool, DEAD};
+
+// Layout, all integers little-endian:
+//
+//   magic    4 bytes "SPST"
+//   version  u32          `StrPool::FORMAT_VERSION`
+//   count    u64          number of slots
+//   slots    count x (kind u8, len u32, len bytes of UTF-8)
+//
+// `kind` is 0 for deduplicated entries, 1 for entries added by `push_heap` and
+// 2 for removed ones, which have no bytes. All slots are kept so that indices
+// survive the round trip. Aliases, canonical keys, hooks and policies are not
+// saved, and static entries are loaded as heap ones.
+const MAGIC: &[u8; 4] = b"SPST";
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+impl StrPool {
+    pub fn save<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&StrPool::FORMAT_VERSION.to_le_bytes())?;
+        w.write_all(&(self.pool.len() as u64).to_le_bytes())?;
+        for entry in &self.pool {
+            let kind = match entry.tag {
+                0 => 0u8,
+                tag if tag & DEAD != 0 => 2,
+                _ => 1,
+            };
+            let len = u32::try_from(entry.len()).map_err(|_| invalid("entry too long to save"))?;
+            w.write_all(&[kind])?;
+            w.write_all(&len.to_le_bytes())?;
+            w.write_all(entry.as_bytes())?;
+        }
+        w.flush()
+    }
+
+    pub fn load<R: Read>(mut r: R) -> io::Result<StrPool> {
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a saved string pool"));
+        }
+        if read_u32(&mut r)? != StrPool::FORMAT_VERSION {
+            return Err(invalid("unsupported string pool format version"));
+        }
+        let mut count = [0; 8];
+        r.read_exact(&mut count)?;
+        let count = u64::from_le_bytes(count);
+
+        let mut pool = StrPool::default();
+        for ptr in 0..count {
+            let mut kind = [0; 1];
+            r.read_exact(&mut kind)?;
+            let len = read_u32(&mut r)? as u64;
+            // Grows with the input rather than trusting `len` up front.
+            let mut buf = Vec::new();
+            (&mut r).take(len).read_to_end(&mut buf)?;
+            if buf.len() as u64 != len {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            let str = String::from_utf8(buf).map_err(|_| invalid("entry is not UTF-8"))?;
+            let loaded = match kind[0] {
+                0 => {
+                    let (loaded, new) = pool.insert(Cow::Owned(str));
+                    if !new {
+                        return Err(invalid("duplicate entry"));
+                    }
+                    loaded
+                }
+                1 => pool.push_heap(str).ptr,
+                2 if str.is_empty() => {
+                    pool.pushed += 1;
+                    let tombstone = Entry { str: Cow::Borrowed(""), tag: DEAD | pool.pushed };
+                    let (loaded, _) = pool.pool.insert_full(tombstone);
+                    #[cfg(feature = "profile")]
+                    pool.resolutions.push(std::sync::atomic::AtomicU64::new(0));
+                    pool.dead += 1;
+                    loaded
+                }
+                _ => return Err(invalid("bad entry kind")),
+            };
+            if loaded as u64 != ptr {
+                return Err(invalid("entry does not keep its index"));
+            }
+        }
+        Ok(pool)
+    }
+}
@@ -0,0 +1,64 @@
+use std::{fmt, ops::Deref};
+
+use crate::StrRef;
+
+// Half the size of a `StrRef`, for structs holding many refs. Only covers the
+// first 2^32 entries of a pool, refs past that fail to convert rather than
+// wrap. Compares by index, which for deduplicated entries matches content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StrRef32(u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolOverflow;
+
+impl fmt::Display for PoolOverflow {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("string pool index does not fit in 32 bits")
+    }
+}
+
+impl std::error::Error for PoolOverflow {}
+
+// Interns into the global pool.
+pub fn intern32(str: &str) -> Result<StrRef32, PoolOverflow> {
+    StrRef32::try_from(crate::get_or_intern(str))
+}
+
+impl StrRef32 {
+    pub const fn as_raw(&self) -> u32 {
+        self.0
+    }
+
+    pub fn to_str_ref(self) -> StrRef {
+        StrRef::new(self.0 as usize)
+    }
+}
+
+impl TryFrom<StrRef> for StrRef32 {
+    type Error = PoolOverflow;
+
+    fn try_from(r: StrRef) -> Result<Self, Self::Error> {
+        u32::try_from(r.ptr).map(StrRef32).map_err(|_| PoolOverflow)
+    }
+}
+
+impl From<StrRef32> for StrRef {
+    fn from(r: StrRef32) -> Self {
+        r.to_str_ref()
+    }
+}
+
+impl Deref for StrRef32 {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        let pool = unsafe { crate::global() };
+        pool.get(self.to_str_ref()).expect("null string ref")
+    }
+}
+
+impl fmt::Display for StrRef32 {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(fmt)
+    }
+}
@@ -0,0 +1,99 @@
+use std::{ops::Deref, str};
+
+use crate::StrRef;
+
+const INLINE_CAP: usize = 7;
+
+// Strings up to 7 bytes are stored inline and never touch the pool.
+#[derive(Clone)]
+pub enum SmallStrRef {
+    Inline([u8; INLINE_CAP], u8),
+    Pooled(StrRef),
+}
+
+impl SmallStrRef {
+    pub fn new(str: &str) -> Self {
+        if str.len() <= INLINE_CAP {
+            let mut buf = [0; INLINE_CAP];
+            buf[..str.len()].copy_from_slice(str.as_bytes());
+            SmallStrRef::Inline(buf, str.len() as u8)
+        } else {
+            SmallStrRef::Pooled(crate::get_or_intern(str))
+        }
+    }
+
+    pub fn is_inline(&self) -> bool {
+        matches!(self, SmallStrRef::Inline(..))
+    }
+}
+
+impl Deref for SmallStrRef {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            SmallStrRef::Inline(buf, len) => unsafe {
+                str::from_utf8_unchecked(&buf[..*len as usize])
+            },
+            SmallStrRef::Pooled(r) => r,
+        }
+    }
+}
+
+impl Default for SmallStrRef {
+    fn default() -> Self {
+        SmallStrRef::new("")
+    }
+}
+
+impl PartialEq<Self> for SmallStrRef {
+    fn eq(&self, other: &Self) -> bool {
+        self.deref() == other.deref()
+    }
+}
+
+impl Eq for SmallStrRef {}
+
+impl PartialEq<str> for SmallStrRef {
+    fn eq(&self, other: &str) -> bool {
+        self.deref() == other
+    }
+}
+
+impl core::hash::Hash for SmallStrRef {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.deref().hash(state);
+    }
+}
+
+impl AsRef<str> for SmallStrRef {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl From<&str> for SmallStrRef {
+    #[inline]
+    fn from(value: &str) -> Self {
+        SmallStrRef::new(value)
+    }
+}
+
+impl From<StrRef> for SmallStrRef {
+    #[inline]
+    fn from(value: StrRef) -> Self {
+        SmallStrRef::Pooled(value)
+    }
+}
+
+impl core::fmt::Debug for SmallStrRef {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(fmt)
+    }
+}
+
+impl core::fmt::Display for SmallStrRef {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(fmt)
+    }
+}
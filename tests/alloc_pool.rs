@@ -0,0 +1,54 @@
+#![cfg(feature = "allocator_api")]
+#![feature(allocator_api)]
+
+use std::{
+    alloc::{AllocError, Allocator, Global, Layout},
+    cell::Cell,
+    ptr::NonNull,
+    rc::Rc,
+};
+
+use strpool::AllocPool;
+
+#[test]
+fn global_allocator() {
+    let mut pool = AllocPool::new();
+    let a = pool.intern("alpha");
+    let b = pool.intern("beta");
+    assert!(pool.intern("alpha").same_index(&a));
+    assert_eq!(pool.get(a), Some("alpha"));
+    assert_eq!(pool.get(b), Some("beta"));
+    assert_eq!(pool.len(), 2);
+}
+
+#[derive(Clone, Default)]
+struct Counting {
+    live: Rc<Cell<usize>>,
+    total: Rc<Cell<usize>>,
+}
+
+unsafe impl Allocator for Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.live.set(self.live.get() + 1);
+        self.total.set(self.total.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.live.set(self.live.get() - 1);
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn strings_come_from_the_given_allocator() {
+    let counting = Counting::default();
+    let mut pool = AllocPool::new_in(counting.clone());
+    let r = pool.intern("counted");
+    pool.intern("counted");
+    pool.intern("also counted");
+    assert_eq!(counting.total.get(), 2);
+    assert_eq!(pool.get(r), Some("counted"));
+    drop(pool);
+    assert_eq!(counting.live.get(), 0);
+}
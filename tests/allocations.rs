@@ -0,0 +1,106 @@
+// Counts heap allocations made by the current thread while a closure runs.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get().map(|n| n + 1)));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get().map(|n| n + 1)));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    ALLOCATIONS.with(|n| n.set(Some(0)));
+    let r = f();
+    (r, ALLOCATIONS.with(|n| n.take()).unwrap())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn deserializing_duplicates_only_allocates_the_vec() {
+    use strpool::StrRef;
+
+    let json = format!("[{}]", vec![r#""word""#; 1000].join(","));
+    strpool::put_static("word");
+    let (refs, n) = allocations(|| serde_json::from_str::<Vec<StrRef>>(&json).unwrap());
+    assert_eq!(refs.len(), 1000);
+    assert!(refs.iter().all(|r| r == "word"));
+    // Growing the `Vec` only, no `String` per element.
+    assert!(n < 32, "{} allocations", n);
+}
+
+#[test]
+fn normalizing_normalized_input_does_not_allocate() {
+    use strpool::StrRef;
+
+    let word = StrRef::from("already normal");
+    let padded = StrRef::from("  padded ");
+    let upper = StrRef::from("Upper");
+    // Warm up the thread locals touched on the way.
+    word.trimmed();
+    let (r, n) = allocations(|| (word.trimmed(), word.to_ascii_lowercase_interned()));
+    assert!(r.0.same_index(&word) && r.1.same_index(&word));
+    assert_eq!(n, 0);
+    let (r, n) = allocations(|| StrRef::from_sanitized("already normal"));
+    assert!(r.same_index(&word));
+    assert_eq!(n, 0);
+    // Trimming borrows a subslice, which interns without a `String` once present.
+    padded.trimmed();
+    let (r, n) = allocations(|| padded.trimmed());
+    assert_eq!(&*r, "padded");
+    assert_eq!(n, 0);
+    let (r, _) = allocations(|| upper.to_ascii_lowercase_interned());
+    assert_eq!(&*r, "upper");
+}
+
+#[test]
+fn intern_cows_stores_borrowed_items_without_copying() {
+    use std::borrow::Cow;
+
+    // Warm up the tables for 3-byte strings.
+    strpool::reserve_global(16);
+    strpool::put_static("hot");
+    let (refs, n) = allocations(|| {
+        strpool::intern_cows([Cow::Borrowed("one"), Cow::Borrowed("two"), Cow::Borrowed("one")])
+    });
+    // Only the returned `Vec`.
+    assert_eq!(n, 1);
+    assert!(refs.iter().all(|r| r.is_static()));
+    assert!(refs[0].same_index(&refs[2]));
+
+    let owned = String::from("three");
+    let addr = owned.as_ptr();
+    let refs = strpool::intern_cows([Cow::Borrowed("two"), Cow::Owned(owned)]);
+    assert!(refs[0].is_static() && !refs[1].is_static());
+    assert_eq!(refs[1].as_ptr(), addr);
+}
+
+#[test]
+fn interned_integers_are_looked_up_without_allocating() {
+    use strpool::IntoStrRef;
+
+    let r = 1234567u64.into_str_ref();
+    let (again, n) = allocations(|| (1234567u32.into_str_ref(), 1234567i64.into_str_ref()));
+    assert!(again.0.same_index(&r) && again.1.same_index(&r));
+    assert_eq!(n, 0);
+}
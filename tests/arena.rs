@@ -0,0 +1,50 @@
+use std::thread;
+
+use strpool::SyncArenaPool;
+
+static POOL: SyncArenaPool = SyncArenaPool::new();
+
+#[test]
+fn threads_intern_and_resolve_concurrently() {
+    let handles: Vec<_> = (0..8)
+        .map(|t| {
+            thread::spawn(move || {
+                let mut seen = Vec::new();
+                for i in 0..2_000 {
+                    // Half of the strings are shared by every thread.
+                    let str = if i % 2 == 0 {
+                        format!("shared-{}", i)
+                    } else {
+                        format!("t{}-{}", t, i)
+                    };
+                    let r = POOL.intern(&str);
+                    let resolved: &'static str = POOL.resolve(r.clone()).unwrap();
+                    assert_eq!(resolved, str);
+                    seen.push((r, str));
+                }
+                // Earlier entries stay put while other threads keep appending.
+                for (r, str) in &seen {
+                    assert_eq!(POOL.resolve(r.clone()), Some(str.as_str()));
+                }
+                seen
+            })
+        })
+        .collect();
+    let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    for (r, str) in results.iter().flatten() {
+        assert!(POOL.intern(str).same_index(r));
+    }
+    assert_eq!(POOL.len(), 1_000 + 8 * 1_000);
+    POOL.debug_check_invariants();
+}
+
+#[test]
+fn resolve_rejects_unpublished_refs() {
+    let pool = SyncArenaPool::new();
+    let r = pool.intern("only");
+    assert_eq!(pool.resolve(r.clone()), Some("only"));
+    assert!(pool.intern("only").same_index(&r));
+    assert_eq!(pool.len(), 1);
+    let other = SyncArenaPool::new();
+    assert_eq!(other.resolve(r), None);
+}
@@ -0,0 +1,10 @@
+#![cfg(feature = "bench")]
+
+#[test]
+fn run_reports_sane_stats() {
+    let stats = strpool::bench::run(&["a", "b", "a", "c"]);
+    assert!(stats.intern_ns_per_op.is_finite() && stats.intern_ns_per_op >= 0.0);
+    assert!(stats.resolve_ns_per_op.is_finite() && stats.resolve_ns_per_op >= 0.0);
+    assert_eq!(stats.dedup_ratio, 0.25);
+    assert_eq!(strpool::bench::run::<&str>(&[]).dedup_ratio, 0.0);
+}
@@ -0,0 +1,22 @@
+#![cfg(feature = "bytes")]
+
+use bytes::Bytes;
+use strpool::StrRef;
+
+#[test]
+fn valid_utf8_bytes_intern() {
+    let r = StrRef::try_from_bytes(Bytes::from_static("héllo".as_bytes())).unwrap();
+    assert_eq!(&*r, "héllo");
+    let bytes = Bytes::from(b"h\xc3\xa9llo".to_vec());
+    let again = StrRef::try_from_bytes(bytes.clone()).unwrap();
+    assert!(again.same_index(&r));
+    // The pool holds no handle to the buffer after a hit.
+    assert!(bytes.is_unique());
+}
+
+#[test]
+fn invalid_utf8_bytes_are_rejected() {
+    let err = StrRef::try_from_bytes(Bytes::from_static(b"ok\xff")).unwrap_err();
+    assert_eq!(err.valid_up_to(), 2);
+    assert_eq!(strpool::Global.len(), 0);
+}
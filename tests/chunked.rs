@@ -0,0 +1,42 @@
+use strpool::ChunkedPool;
+
+#[test]
+fn strings_span_many_chunks() {
+    let mut pool = ChunkedPool::default();
+    // About 200KB, so at least four 64KB chunks.
+    let strings: Vec<String> = (0..20_000).map(|i| format!("string-{:04}", i)).collect();
+    let refs: Vec<_> = strings.iter().map(|s| pool.put(s)).collect();
+    let big = "x".repeat(100 * 1024);
+    let big_ref = pool.put(&big);
+    for (r, s) in refs.iter().zip(&strings) {
+        assert_eq!(pool.get(r.clone()), Some(s.as_str()));
+    }
+    assert_eq!(pool.get(big_ref), Some(big.as_str()));
+    assert_eq!(pool.len(), strings.len() + 1);
+    assert!(pool.put(&strings[7]).same_index(&refs[7]));
+}
+
+#[test]
+fn substrings_share_the_parent_bytes() {
+    let mut pool = ChunkedPool::default();
+    let parent = pool.put("hello wörld");
+    let hello = pool.intern_substr_of(parent.clone(), 0..5);
+    let world = pool.intern_substr_of(parent.clone(), 6..12);
+    let whole = pool.get(parent.clone()).unwrap();
+    assert_eq!(pool.get(hello.clone()), Some("hello"));
+    assert_eq!(pool.get(world.clone()), Some("wörld"));
+    assert_eq!(pool.get(hello.clone()).unwrap().as_ptr(), whole.as_ptr());
+    assert_eq!(pool.get(world).unwrap().as_ptr(), whole[6..].as_ptr());
+    // Already interned substrings are looked up like any other string.
+    assert!(pool.put("hello").same_index(&hello));
+    assert!(pool.intern_substr_of(parent, 0..5).same_index(&hello));
+    assert_eq!(pool.len(), 3);
+}
+
+#[test]
+#[should_panic(expected = "is not a char boundary")]
+fn substrings_must_fall_on_char_boundaries() {
+    let mut pool = ChunkedPool::default();
+    let parent = pool.put("wörld");
+    pool.intern_substr_of(parent, 0..2);
+}
@@ -0,0 +1,21 @@
+#![cfg(feature = "compact_str")]
+
+use compact_str::CompactString;
+use strpool::StrRef;
+
+#[test]
+fn inline_and_heap_compact_strings_intern() {
+    let inline = CompactString::new("short");
+    assert!(!inline.is_heap_allocated());
+    let r = StrRef::from(inline);
+    assert_eq!(&*r, "short");
+    assert!(StrRef::from(CompactString::new("short")).same_index(&r));
+
+    let long = "a compact string too long to be stored inline".repeat(2);
+    let heap = CompactString::from(long.clone());
+    assert!(heap.is_heap_allocated());
+    let r = StrRef::from(heap);
+    assert_eq!(&*r, long);
+    assert!(StrRef::from(long.as_str()).same_index(&r));
+    assert_eq!(CompactString::from(r), long);
+}
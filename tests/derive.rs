@@ -0,0 +1,22 @@
+#![cfg(feature = "derive")]
+
+use strpool::{Intern, StrRef};
+
+#[derive(Intern)]
+struct User<'a> {
+    name: String,
+    role: &'a str,
+    age: u32,
+}
+
+#[test]
+fn derived_intern_turns_string_fields_into_refs() {
+    let role = String::from("admin");
+    let user = User { name: "alice".to_owned(), role: &role, age: 30 };
+    let interned: UserInterned = user.intern();
+    let name: StrRef = interned.name;
+    assert_eq!(&*name, "alice");
+    assert_eq!(&*interned.role, "admin");
+    assert!(interned.role.same_index(&StrRef::from("admin")));
+    assert_eq!(interned.age, 30);
+}
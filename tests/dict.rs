@@ -0,0 +1,30 @@
+use strpool::DictEncoder;
+
+#[test]
+fn column_round_trips_through_dense_codes() {
+    let column = ["red", "green", "red", "blue", "green", "red"];
+    let mut encoder = DictEncoder::new();
+    let codes: Vec<u32> = column.iter().map(|s| encoder.encode(s)).collect();
+    assert_eq!(codes, [0, 1, 0, 2, 1, 0]);
+    let decoded: Vec<&str> = codes.iter().map(|&c| encoder.decode(c).unwrap()).collect();
+    assert_eq!(decoded, column);
+    assert_eq!(encoder.len(), 3);
+    assert_eq!(encoder.dictionary().collect::<Vec<_>>(), ["red", "green", "blue"]);
+    assert_eq!(encoder.decode(3), None);
+    // Codes stay put as the dictionary grows.
+    assert_eq!(encoder.encode("yellow"), 3);
+    assert_eq!(encoder.encode("green"), 1);
+}
+
+#[test]
+fn encode_column_matches_per_value_codes() {
+    let column = ["b", "a", "b", "c", "a", "b"];
+    let mut batch = DictEncoder::new();
+    let codes = batch.encode_column(&column);
+    let mut single = DictEncoder::new();
+    let expected: Vec<u32> = column.iter().map(|s| single.encode(s)).collect();
+    assert_eq!(codes, expected);
+    let decoded: Vec<&str> = codes.iter().map(|&c| batch.decode(c).unwrap()).collect();
+    assert_eq!(decoded, column);
+    assert_eq!(batch.encode_column(&["c", "d"]), [2, 3]);
+}
@@ -0,0 +1,129 @@
+use strpool::{
+    FrozenPool, FrozenPoolError, LayeredRef, OverlayPool, SortedFrozenPool, StrPool, StrRef,
+};
+
+fn leak(bytes: &[u8]) -> &'static [u8] {
+    Box::leak(bytes.to_vec().into_boxed_slice())
+}
+
+fn sample() -> StrPool {
+    let mut pool = StrPool::default();
+    for s in ["alpha", "", "gamma", "δέλτα"] {
+        pool.put_static(s);
+    }
+    pool
+}
+
+#[test]
+fn bytes_round_trip() {
+    let pool = sample();
+    let frozen = FrozenPool::from_pool(&pool);
+    let loaded = FrozenPool::from_bytes(leak(frozen.to_bytes())).unwrap();
+    assert_eq!(loaded.len(), 4);
+    assert_eq!(loaded.to_bytes(), frozen.to_bytes());
+    for (ptr, s) in pool.entries_in_order() {
+        assert_eq!(loaded.get(StrRef::from_raw(ptr)), Some(s));
+    }
+    assert_eq!(loaded.get(StrRef::from_raw(4)), None);
+    assert_eq!(loaded.get_ref("gamma").map(|r| r.as_raw()), Some(2));
+    assert!(loaded.get_ref("beta").is_none());
+}
+
+#[test]
+fn from_bytes_rejects_bad_input() {
+    let bytes = FrozenPool::from_pool(&sample()).to_bytes().to_vec();
+    let truncated = leak(&bytes[..bytes.len() - 1]);
+    assert_eq!(FrozenPool::from_bytes(truncated).err(), Some(FrozenPoolError::Truncated));
+    let mut magic = bytes.clone();
+    magic[0] = b'X';
+    assert_eq!(FrozenPool::from_bytes(leak(&magic)).err(), Some(FrozenPoolError::BadMagic));
+    let mut invalid = bytes.clone();
+    let at = invalid.len() - 1;
+    invalid[at] = 0xff;
+    assert!(matches!(FrozenPool::from_bytes(leak(&invalid)), Err(FrozenPoolError::Utf8(_))));
+}
+
+#[test]
+fn from_bytes_rejects_other_versions() {
+    let mut bytes = FrozenPool::from_pool(&sample()).to_bytes().to_vec();
+    let (expected, found) = (StrPool::FORMAT_VERSION, StrPool::FORMAT_VERSION + 1);
+    bytes[4..8].copy_from_slice(&found.to_le_bytes());
+    let expected = FrozenPoolError::VersionMismatch { found, expected };
+    assert_eq!(FrozenPool::from_bytes(leak(&bytes)).err(), Some(expected));
+}
+
+#[test]
+fn ref_of_ptr_finds_the_resolved_entry() {
+    let frozen = FrozenPool::from_pool(&sample());
+    for ptr in 0..frozen.len() {
+        let s = frozen.get(StrRef::from_raw(ptr)).unwrap();
+        assert_eq!(frozen.ref_of_ptr(s).map(|r| r.as_raw()), Some(ptr));
+    }
+    let gamma = frozen.get(StrRef::from_raw(2)).unwrap();
+    assert!(frozen.ref_of_ptr(&gamma[1..]).is_none());
+    assert!(frozen.ref_of_ptr("gamma").is_none());
+}
+
+#[test]
+fn overlay_only_stores_strings_missing_from_the_base() {
+    let base = std::sync::Arc::new(FrozenPool::from_pool(&sample()));
+    let mut pool = OverlayPool::new(base);
+    let hit = pool.intern("gamma");
+    assert!(matches!(&hit, LayeredRef::Base(r) if r.as_raw() == 2));
+    assert_eq!(pool.overlay().len(), 0);
+
+    let miss = pool.intern("epsilon");
+    assert!(matches!(&miss, LayeredRef::Local(_)));
+    let again = pool.intern("epsilon");
+    match (&miss, &again) {
+        (LayeredRef::Local(a), LayeredRef::Local(b)) => assert!(a.same_index(b)),
+        _ => unreachable!(),
+    }
+    assert_eq!(pool.overlay().len(), 1);
+
+    assert_eq!(pool.resolve(hit), Some("gamma"));
+    assert_eq!(pool.resolve(miss), Some("epsilon"));
+}
+
+#[test]
+fn rehome_copies_single_entries_into_a_live_pool() {
+    let bytes = leak(FrozenPool::from_pool(&sample()).to_bytes());
+    let mapped = FrozenPool::from_bytes(bytes).unwrap();
+    let built = FrozenPool::from_pool(&sample());
+    let mut live = StrPool::default();
+    live.put_static("zeta");
+    let gamma = live.rehome_from(&mapped, StrRef::from_raw(2)).unwrap();
+    assert_eq!(live.get(gamma.clone()), Some("gamma"));
+    // Loaded bytes outlive the pool and are borrowed, not copied.
+    assert!(live.is_static(gamma.clone()).unwrap());
+    let source = mapped.get(StrRef::from_raw(2)).unwrap();
+    assert_eq!(live.get(gamma.clone()).unwrap().as_ptr(), source.as_ptr());
+    let again = live.rehome_from(&built, StrRef::from_raw(2)).unwrap();
+    assert!(again.same_index(&gamma));
+    let delta = live.rehome_from(&built, StrRef::from_raw(3)).unwrap();
+    assert_eq!(live.is_static(delta.clone()), Some(false));
+    assert_eq!(live.get(delta), Some("δέλτα"));
+    assert_eq!(live.rehome_from(&built, StrRef::from_raw(4)), None);
+    assert_eq!(live.len(), 3);
+}
+
+#[test]
+fn sorted_pool_looks_up_by_binary_search() {
+    let mut pool = StrPool::default();
+    let refs: Vec<StrRef> = ["pear", "apple", "fig"].map(|s| pool.put_static(s)).to_vec();
+    let pushed = pool.push_heap("apple".to_owned());
+    let sorted: SortedFrozenPool = pool.into_sorted();
+    assert_eq!(sorted.len(), 4);
+    for (r, s) in refs.iter().zip(["pear", "apple", "fig"]) {
+        assert!(sorted.get_ref(s).unwrap().same_index(r));
+        assert_eq!(sorted.get(r.clone()), Some(s));
+    }
+    // Both copies keep their content, lookups give the lower index.
+    assert_eq!(sorted.get(pushed), Some("apple"));
+    for missing in ["", "apples", "banana", "zzz"] {
+        assert_eq!(sorted.get_ref(missing), None);
+    }
+    assert_eq!(sorted.get(StrRef::from_raw(4)), None);
+    let order: Vec<_> = sorted.iter_sorted().map(|(r, s)| (s, r.as_raw())).collect();
+    assert_eq!(order, [("apple", 1), ("apple", 3), ("fig", 2), ("pear", 0)]);
+}
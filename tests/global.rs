@@ -0,0 +1,587 @@
+use std::collections::HashMap;
+
+use strpool::StrRef;
+
+#[test]
+fn from_mut_str_and_string_ref() {
+    let mut owned = String::from("conv");
+    let a = StrRef::from(owned.as_mut_str());
+    let b = StrRef::from(&owned);
+    assert!(a.same_index(&b));
+    assert_eq!(&*b, "conv");
+}
+
+#[test]
+fn read_guard_resolves_like_deref() {
+    let refs: Vec<StrRef> = ["a", "bb", "ccc"].into_iter().map(StrRef::from).collect();
+    let guard = strpool::read_guard();
+    for r in &refs {
+        assert_eq!(guard.resolve(r.clone()), &**r);
+    }
+}
+
+#[test]
+fn intern_fmt_caches_per_call_site() {
+    let render = |n: u32| strpool::intern_fmt!("frame:{}", n);
+    let first = render(1);
+    let (input, _) = strpool::Global.savings();
+    // Unchanged output is served by the call site without a pool lookup,
+    // which would count towards the input bytes.
+    let again = render(1);
+    assert!(again.same_index(&first));
+    assert_eq!(strpool::Global.savings().0, input);
+    let changed = render(2);
+    assert_eq!(&*changed, "frame:2");
+    assert!(strpool::Global.savings().0 > input);
+    assert!(render(1).same_index(&first));
+}
+
+#[test]
+fn is_static_tells_entry_kinds_apart() {
+    assert!(strpool::put_static("literal").is_static());
+    assert!(!strpool::put_heap("heap".to_owned()).is_static());
+}
+
+#[test]
+fn intern_with_str_agrees_with_ref() {
+    let (r, s) = strpool::intern_with_str("both".to_owned());
+    assert_eq!(s, "both");
+    assert_eq!(&*r, s);
+    assert!(core::ptr::eq(s, &*r));
+}
+
+#[test]
+fn ascii_case_insensitive_comparison() {
+    use std::cmp::Ordering;
+
+    let r = StrRef::from("Hello");
+    assert!(r.eq_ignore_ascii_case("hELLO"));
+    assert!(!r.eq_ignore_ascii_case("hello!"));
+    assert_eq!(r.cmp_ignore_ascii_case("HELLO"), Ordering::Equal);
+    assert_eq!(r.cmp_ignore_ascii_case("help"), Ordering::Less);
+    // Only ASCII letters are folded.
+    let r = StrRef::from("Straße");
+    assert!(r.eq_ignore_ascii_case("STRAßE"));
+    assert!(!r.eq_ignore_ascii_case("STRASSE"));
+    assert!(!StrRef::from("é").eq_ignore_ascii_case("É"));
+}
+
+#[test]
+fn from_reader_respects_cap() {
+    use std::io::{Cursor, ErrorKind};
+
+    let r = StrRef::from_reader(Cursor::new("file contents"), 13).unwrap();
+    assert_eq!(&*r, "file contents");
+    let err = StrRef::from_reader(Cursor::new("file contents"), 12).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    let err = StrRef::from_reader(Cursor::new(b"\xff\xfe"), 16).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn resolve_or_empty_handles_none() {
+    use strpool::OptStrRefExt;
+
+    assert_eq!(Some(StrRef::from("set")).resolve_or_empty(), "set");
+    assert_eq!(None::<StrRef>.resolve_or_empty(), "");
+}
+
+#[test]
+fn frozen_global_still_finds_existing_strings() {
+    let known = strpool::put_static("known");
+    strpool::freeze_global();
+    assert!(strpool::put_heap("known".to_owned()).same_index(&known));
+    assert!(strpool::get_or_intern("known").same_index(&known));
+}
+
+#[test]
+#[should_panic(expected = "frozen")]
+fn frozen_global_rejects_new_strings() {
+    strpool::put_static("known");
+    strpool::freeze_global();
+    strpool::put_heap("unknown".to_owned());
+}
+
+#[test]
+fn cow_from_ref_borrows_static_entries() {
+    use std::borrow::Cow;
+
+    let borrowed = Cow::from(strpool::put_static("static"));
+    assert!(matches!(borrowed, Cow::Borrowed("static")));
+    let owned: Cow<'static, str> = strpool::put_heap("heap".to_owned()).into();
+    assert!(matches!(owned, Cow::Owned(ref s) if s == "heap"));
+}
+
+#[test]
+fn assert_eq_reports_mismatches() {
+    let r = StrRef::from("expected");
+    assert_eq!(r.assert_eq("expected"), Ok(()));
+    assert_eq!(
+        r.assert_eq("expectet"),
+        Err(r#"expected "expectet", found "expected" (first difference at byte 7)"#.to_owned())
+    );
+    let err = StrRef::from("ab").assert_eq("abc").unwrap_err();
+    assert!(err.ends_with("(first difference at byte 2)"), "{}", err);
+}
+
+#[test]
+fn padding() {
+    let r = StrRef::from("ab");
+    assert_eq!(&*r.pad_right(4, '.'), "ab..");
+    assert_eq!(&*r.pad_left(4, ' '), "  ab");
+    assert!(r.pad_left(2, '.').same_index(&r));
+    assert!(r.pad_right(1, '.').same_index(&r));
+    // Widths count chars, not bytes.
+    assert_eq!(&*r.pad_right(4, '—'), "ab——");
+    assert_eq!(&*StrRef::from("né").pad_left(3, '·'), "·né");
+}
+
+#[test]
+fn display_truncated() {
+    let r = StrRef::from("héllo world");
+    assert_eq!(r.display_truncated(5).to_string(), "héll…");
+    // Cut before the two-byte `é` rather than through it.
+    assert_eq!(r.display_truncated(2).to_string(), "h…");
+    assert_eq!(r.display_truncated(12).to_string(), "héllo world");
+    assert_eq!(r.display_truncated(100).to_string(), "héllo world");
+}
+
+#[test]
+fn join_to_string_matches_naive_join() {
+    let parts: Vec<StrRef> = ["usr", "local", "bin"].into_iter().map(StrRef::from).collect();
+    let naive = parts.iter().map(|r| &**r).collect::<Vec<_>>().join("/");
+    assert_eq!(strpool::join_to_string(&parts, "/"), naive);
+    assert_eq!(strpool::join_to_string(&parts[..1], ", "), "usr");
+    assert_eq!(strpool::join_to_string(&[], ", "), "");
+}
+
+#[test]
+fn reset_heap_only_keeps_statics_and_remaps_them() {
+    let heap = strpool::put_heap("heap".to_owned());
+    let first = strpool::put_static("first");
+    let second = strpool::put_static("second");
+    let slice: &str = &heap;
+    let remap = strpool::reset_heap_only();
+    assert_eq!(remap.get(&heap), None);
+    let (first, second) = (remap.get(&first).unwrap(), remap.get(&second).unwrap());
+    assert_eq!((&*first, &*second), ("first", "second"));
+    assert_eq!((first.ordinal(), second.ordinal()), (0, 1));
+    // The dropped entry's buffer is leaked, not freed.
+    assert_eq!(slice, "heap");
+}
+
+#[test]
+fn segments_round_trip() {
+    let segs = ["ns", "table", "col"];
+    let r = StrRef::from_segments(&segs, '.');
+    assert_eq!(&*r, "ns.table.col");
+    assert!(r.segments('.').eq(segs));
+    assert_eq!(StrRef::from_segments(&["ns", "table", "col"], '.'), r);
+    assert!(StrRef::from_segments(&[], '.').segments('.').eq([""]));
+}
+
+#[test]
+fn resolve_cache_is_invalidated_by_resets() {
+    let old = strpool::put_heap("before".to_owned());
+    assert_eq!(&*old, "before");
+    strpool::reset_heap_only();
+    let new = strpool::put_heap("after".to_owned());
+    assert!(new.same_index(&old));
+    assert_eq!(&*old, "after");
+    assert_eq!(&*new, "after");
+}
+
+#[test]
+fn scoped_pool_leaves_the_outer_pool_alone() {
+    let outer = strpool::put_static("outer");
+    let slice: &'static str = strpool::with_scoped_pool(|| {
+        assert_eq!(strpool::Global.len(), 0);
+        let inner = strpool::put_heap("inner".to_owned());
+        strpool::put_heap("outer".to_owned());
+        assert_eq!(strpool::Global.len(), 2);
+        // A slice can outlive the scope in safe code through a leaked ref.
+        let inner: &'static StrRef = Box::leak(Box::new(inner));
+        &**inner
+    });
+    assert_eq!(strpool::Global.len(), 1);
+    assert_eq!(&*outer, "outer");
+    assert_eq!(slice, "inner");
+}
+
+#[test]
+fn dangling_refs_compare_unequal() {
+    let kept = strpool::put_static("kept");
+    let dropped = strpool::put_heap("dropped".to_owned());
+    strpool::reset_heap_only();
+    assert!(!dropped.is_resolvable());
+    assert_ne!(dropped, kept);
+    assert_ne!(kept, dropped);
+    assert_eq!(dropped, dropped.clone());
+    assert_ne!(dropped, StrRef::from_raw(dropped.as_raw() + 1));
+    assert_eq!(kept, StrRef::from("kept"));
+}
+
+#[test]
+fn reserving_grows_capacity() {
+    let before = strpool::Global.capacity();
+    strpool::reserve_global(before + 100);
+    assert!(strpool::Global.capacity() >= before + 100);
+    let reserved = {
+        let _reserve = strpool::GlobalReserveGuard::new(10_000);
+        for i in 0..50 {
+            strpool::put_heap(format!("batch{}", i));
+        }
+        strpool::Global.capacity()
+    };
+    assert!(reserved >= 10_000);
+    assert_eq!(strpool::Global.capacity(), reserved);
+    strpool::shrink_global();
+    let after = strpool::Global.capacity();
+    assert!(after < reserved && after >= strpool::Global.len());
+}
+
+#[test]
+fn intern_concat_matches_manual_concatenation() {
+    let r = strpool::intern_concat(["usr", "/", "bin"]);
+    assert!(r.same_index(&StrRef::from("usr/bin")));
+    // Not `Clone`, and longer than the parts sized up front.
+    let mut n = 0;
+    let parts = std::iter::from_fn(|| {
+        n += 1;
+        (n <= 20).then_some("ab")
+    });
+    assert_eq!(&*strpool::intern_concat(parts), "ab".repeat(20));
+    assert_eq!(&*strpool::intern_concat([]), "");
+}
+
+#[test]
+fn from_display_buf_reuses_the_buffer() {
+    use std::time::Duration;
+
+    let mut buf = String::with_capacity(64);
+    let start = buf.as_ptr();
+    let a = StrRef::from_display_buf(&format_args!("{:?}", Duration::from_millis(1500)), &mut buf);
+    let b = StrRef::from_display_buf(&42, &mut buf);
+    let c = StrRef::from_display_buf(&format_args!("{:?}", Duration::from_secs(1)), &mut buf);
+    let again = StrRef::from_display_buf(&42, &mut buf);
+    assert_eq!((&*a, &*b, &*c), ("1.5s", "42", "1s"));
+    assert!(again.same_index(&b));
+    assert_eq!(buf, "42");
+    assert_eq!(buf.as_ptr(), start);
+}
+
+#[test]
+fn stable_hash_is_the_same_everywhere() {
+    use std::hash::{Hash, Hasher};
+    use strpool::StableHash;
+
+    // Keeps what is fed to it, which for `StableHash` must not depend on the
+    // pool or process.
+    #[derive(Default)]
+    struct Recorder(Vec<u8>);
+
+    impl Hasher for Recorder {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.extend_from_slice(bytes);
+        }
+    }
+
+    let fed = |r: StrRef| {
+        let mut recorder = Recorder::default();
+        StableHash(r).hash(&mut recorder);
+        recorder.0
+    };
+    strpool::put_static("padding");
+    let here = fed(StrRef::from("a"));
+    let there = std::thread::spawn(move || fed(StrRef::from("a"))).join().unwrap();
+    assert_eq!(here, there);
+    // The FNV-1a test vector.
+    assert_eq!(StableHash(StrRef::from("a")).stable_hash(), 0xaf63_dc4c_8601_ec8c);
+    assert_eq!(here, 0xaf63_dc4c_8601_ec8cu64.to_ne_bytes());
+}
+
+#[test]
+fn ordinals_follow_distinct_insertions() {
+    let words = ["one", "two", "one", "three", "two", "four"];
+    let ordinals: Vec<_> = words.iter().map(|s| StrRef::from(*s).ordinal()).collect();
+    assert_eq!(ordinals, [0, 1, 0, 2, 1, 3]);
+}
+
+#[test]
+fn ordinals_skip_removed_entries() {
+    let first = StrRef::from("first");
+    drop(strpool::intern_scoped("removed"));
+    let third = StrRef::from("third");
+    assert_eq!((third.as_raw(), third.bit_index()), (2, 2));
+    assert_eq!((first.ordinal(), third.ordinal()), (0, 1));
+}
+
+#[test]
+fn pathological_bytes_are_rejected_without_panicking() {
+    use strpool::FromBytesError;
+
+    let text = "ünïcödé";
+    let mut bytes = text.as_bytes().to_vec();
+    for at in 0..bytes.len() {
+        let saved = bytes[at];
+        bytes[at] = 0xff;
+        let err = StrRef::try_from(&bytes[..]).unwrap_err();
+        let boundary = (0..=at).rev().find(|&i| text.is_char_boundary(i));
+        assert_eq!(Some(err.valid_up_to()), boundary);
+        assert!(StrRef::try_from(bytes.clone()).is_err());
+        bytes[at] = saved;
+    }
+    // Cut inside a multibyte sequence.
+    assert!(StrRef::try_from(&bytes[..1]).is_err());
+
+    let huge = vec![b'a'; 1 << 20];
+    let err = StrRef::from_utf8_capped(&huge, 4096).unwrap_err();
+    assert!(matches!(err, FromBytesError::TooLong { len, max: 4096 } if len == huge.len()));
+    assert_eq!(strpool::Global.len(), 0);
+}
+
+#[test]
+fn dropping_the_last_scoped_guard_removes_the_entry() {
+    let kept = StrRef::from("kept");
+    let (r, slice) = {
+        let scoped = strpool::intern_scoped("request-42");
+        let other = strpool::intern_scoped("request-42");
+        assert_eq!(&*scoped, "request-42");
+        drop(other);
+        assert!(scoped.str_ref().is_resolvable());
+        let r: &'static StrRef = Box::leak(Box::new(scoped.str_ref().clone()));
+        (r, &**r)
+    };
+    assert!(!r.is_resolvable());
+    assert_eq!(&*kept, "kept");
+    assert_eq!(strpool::Global.len(), 1);
+    // Only the entry went away, the bytes a clone of the ref handed out are
+    // still there.
+    assert_eq!(slice, "request-42");
+    // Strings interned before stay when a guard for them is dropped.
+    drop(strpool::intern_scoped("kept"));
+    assert!(kept.is_resolvable());
+}
+
+#[test]
+fn comparing_with_byte_slices() {
+    let r = StrRef::from("foo");
+    assert!(r == b"foo"[..]);
+    let (same, invalid): (&[u8], &[u8]) = (b"foo", b"foo\xff");
+    assert!(r == same);
+    assert!(r != b"fo"[..]);
+    assert!(r != invalid);
+    assert!(StrRef::from("\u{fffd}") != b"\xff"[..]);
+}
+
+#[test]
+fn escape_debug_quotes_and_escapes() {
+    let r = StrRef::from("say \"hi\"\n\tcafé\u{0}\u{200b}");
+    let before = strpool::Global.len();
+    let escaped = r.escape_debug_display().to_string();
+    assert_eq!(escaped, r#""say \"hi\"\n\tcafé\0\u{200b}""#);
+    assert_eq!(strpool::Global.len(), before);
+}
+
+#[test]
+fn from_sanitized_replaces_nul_only_when_present() {
+    let clean = StrRef::from("clean log line");
+    assert!(StrRef::from_sanitized("clean log line").same_index(&clean));
+    let sanitized = StrRef::from_sanitized("bad\0line\0");
+    assert_eq!(&*sanitized, "bad\u{FFFD}line\u{FFFD}");
+    assert!(StrRef::from_sanitized("bad\u{FFFD}line\u{FFFD}").same_index(&sanitized));
+    assert!(StrRef::from_sanitized("bad\0line\u{FFFD}").same_index(&sanitized));
+}
+
+#[test]
+fn utf16_decoding() {
+    let units: Vec<u16> = "héllo 🦀".encode_utf16().collect();
+    let r = StrRef::from_utf16(&units).unwrap();
+    assert_eq!(&*r, "héllo 🦀");
+    assert!(StrRef::from("héllo 🦀").same_index(&r));
+    // An unpaired high surrogate.
+    let broken = [0x0061, 0xD800, 0x0062];
+    assert!(StrRef::from_utf16(&broken).is_err());
+    assert_eq!(&*StrRef::from_utf16_lossy(&broken), "a\u{FFFD}b");
+    assert!(StrRef::from_utf16_lossy(&units).same_index(&r));
+}
+
+#[test]
+fn refs_from_other_threads_are_not_resolvable() {
+    let foreign = std::thread::spawn(|| {
+        let refs: Vec<StrRef> = (0..100).map(|i| strpool::put_heap(format!("t{}", i))).collect();
+        assert!(refs.iter().all(StrRef::is_resolvable));
+        refs.into_iter().last().unwrap()
+    })
+    .join()
+    .unwrap();
+    assert!(!foreign.is_resolvable());
+    assert!(StrRef::from("local").is_resolvable());
+}
+
+// Thread local destructors run in reverse order of registration on Linux, so
+// the probe, registered first, is dropped after the global pool.
+#[cfg(target_os = "linux")]
+#[test]
+fn try_global_is_none_after_the_pool_is_torn_down() {
+    use std::{cell::Cell, sync::mpsc};
+
+    struct Probe(Cell<Option<mpsc::Sender<Option<usize>>>>);
+
+    impl Drop for Probe {
+        fn drop(&mut self) {
+            let _ = self.0.take().unwrap().send(strpool::try_global(|pool| pool.len()));
+        }
+    }
+
+    thread_local! {
+        static PROBE: Probe = const { Probe(Cell::new(None)) };
+    }
+
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        PROBE.with(|probe| probe.0.set(Some(tx)));
+        strpool::put_static("alive");
+        assert_eq!(strpool::try_global(|pool| pool.len()), Some(1));
+    })
+    .join()
+    .unwrap();
+    assert_eq!(rx.recv().unwrap(), None);
+}
+
+#[test]
+fn dedup_runs_collapses_consecutive_refs_only() {
+    let [a, b] = ["a", "b"].map(StrRef::from);
+    let mut refs = vec![a.clone(), a.clone(), b.clone(), a.clone()];
+    strpool::dedup_runs(&mut refs);
+    assert_eq!(refs.len(), 3);
+    assert!(refs[0].same_index(&a) && refs[1].same_index(&b) && refs[2].same_index(&a));
+}
+
+#[test]
+fn leaked_strs_outlive_resets_and_threads() {
+    let r = strpool::put_heap("target".to_owned());
+    let target: &'static str = r.leak();
+    assert!(r.is_static());
+    assert_eq!(r.leak().as_ptr(), target.as_ptr());
+    strpool::reset_heap_only();
+    let len = std::thread::spawn(move || target.len()).join().unwrap();
+    assert_eq!((target, len), ("target", 6));
+}
+
+#[test]
+fn intern_map_dedups_across_keys_and_values() {
+    let map: HashMap<String, String> = [("en", "hello"), ("fr", "bonjour"), ("hello", "en")]
+        .into_iter()
+        .map(|(k, v)| (k.to_owned(), v.to_owned()))
+        .collect();
+    let before = strpool::Global.len();
+    let interned = strpool::intern_map(map);
+    assert_eq!(strpool::Global.len(), before + 4);
+    let [en, fr, hello] = ["en", "fr", "hello"].map(StrRef::from);
+    assert_eq!(interned.len(), 3);
+    assert!(interned[&en].same_index(&hello));
+    assert!(interned[&hello].same_index(&en));
+    assert_eq!(&*interned[&fr], "bonjour");
+}
+
+#[test]
+fn into_vec_u8_copies_the_resolved_bytes() {
+    let r = StrRef::from("bytés");
+    let bytes = Vec::<u8>::from(r.clone());
+    assert_eq!(bytes, "bytés".as_bytes());
+    assert_eq!(StrRef::try_from(bytes).unwrap(), r);
+}
+
+#[cfg(unix)]
+#[test]
+fn path_components_dedup_across_paths() {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt, path::Path};
+
+    let refs = strpool::intern_path_components(Path::new("a/b/a/c"));
+    let names: Vec<&str> = refs.iter().map(|r| &**r).collect();
+    assert_eq!(names, ["a", "b", "a", "c"]);
+    assert!(refs[0].same_index(&refs[2]));
+    let rooted = strpool::try_intern_path_components(Path::new("/a/c")).unwrap();
+    assert_eq!(&*rooted[0], "/");
+    assert!(rooted[1].same_index(&refs[0]) && rooted[2].same_index(&refs[3]));
+    let invalid = Path::new(OsStr::from_bytes(b"a/\xff"));
+    assert_eq!(strpool::try_intern_path_components(invalid), None);
+    assert_eq!(&*strpool::intern_path_components(invalid)[1], "\u{FFFD}");
+}
+
+#[test]
+fn mixed_keys_intern_through_into_str_ref() {
+    use strpool::IntoStrRef;
+
+    fn key(k: impl IntoStrRef) -> StrRef {
+        k.into_str_ref()
+    }
+
+    let seven = key("7");
+    assert!(key(String::from("7")).same_index(&seven));
+    let owned = String::from("7");
+    assert!(key(&owned).same_index(&seven));
+    assert!(key(String::from("7").as_mut_str()).same_index(&seven));
+    assert!(key(Box::<str>::from("7")).same_index(&seven));
+    assert!(key(7u64).same_index(&seven));
+    assert!(key('7').same_index(&seven));
+    assert!(key(seven.clone()).same_index(&seven));
+    assert!(key(7u8).same_index(&seven) && key(7i32).same_index(&seven));
+    assert!(key(7usize).same_index(&seven) && key(7i128).same_index(&seven));
+    assert_eq!(&*key(u64::MAX), "18446744073709551615");
+    assert_eq!(&*key(-1i8), "-1");
+    assert_eq!(&*key(i128::MIN), i128::MIN.to_string());
+    assert_eq!(&*key('é'), "é");
+}
+
+#[test]
+fn deferred_refs_validate_on_first_resolve() {
+    let deferred = strpool::DeferredRef::new(b"deferred");
+    let before = strpool::Global.len();
+    let r = deferred.resolve().unwrap();
+    assert_eq!(&*r, "deferred");
+    assert!(r.is_static());
+    let (input, _) = strpool::Global.savings();
+    // Served from the cache, without a pool lookup counting towards input.
+    assert!(deferred.resolve().unwrap().same_index(&r));
+    assert_eq!(strpool::Global.savings().0, input);
+    assert_eq!(strpool::Global.len(), before + 1);
+
+    let invalid = strpool::DeferredRef::new(b"bad \xff");
+    assert_eq!(invalid.resolve().unwrap_err().valid_up_to(), 4);
+    assert!(invalid.resolve().is_err());
+    assert_eq!(strpool::Global.len(), before + 1);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "refs only resolve in the global pool of the thread that made them")]
+fn cross_thread_deref_panics_in_debug_builds() {
+    let foreign = std::thread::spawn(|| StrRef::from("foreign")).join().unwrap();
+    // The same index is live here too, so without the check this would resolve.
+    assert!(StrRef::from("foreign").is_resolvable());
+    let _ = foreign.len();
+}
+
+#[test]
+fn global_hook_cannot_reenter_the_pool() {
+    use std::panic::{self, AssertUnwindSafe};
+
+    strpool::set_global_on_insert(Box::new(|_, s| {
+        if s == "reenter" {
+            strpool::put_static("from the hook");
+        }
+    }));
+    strpool::put_static("fine");
+    let err = panic::catch_unwind(AssertUnwindSafe(|| strpool::put_static("reenter")));
+    let err = err.unwrap_err();
+    assert_eq!(err.downcast_ref::<&str>(), Some(&"global pool used from an insert hook"));
+    // The flag is reset on the way out.
+    assert_eq!(&*strpool::put_static("after"), "after");
+    assert!(strpool::try_global(|pool| pool.len()).is_some());
+}
@@ -0,0 +1,25 @@
+strpool::interned_enum! {
+    Keyword { If = "if", Else = "else", While = "while" }
+}
+
+#[test]
+fn tokens_match_by_index() {
+    assert_eq!(Keyword::ALL, [Keyword::If, Keyword::Else, Keyword::While]);
+    assert_eq!(Keyword::Else as usize, 1);
+    let tokens: Vec<_> = "while if else for".split(' ').map(strpool::StrRef::from).collect();
+    let matched: Vec<_> = tokens.iter().map(Keyword::from_str_ref).collect();
+    assert_eq!(matched, [Some(Keyword::While), Some(Keyword::If), Some(Keyword::Else), None]);
+    assert!(Keyword::If.str_ref().same_index(&tokens[1]));
+    assert_eq!(Keyword::from_str("else"), Some(Keyword::Else));
+    assert_eq!(Keyword::While.as_str(), "while");
+}
+
+#[test]
+fn str_ref_is_cached_per_variant() {
+    let first = Keyword::While.str_ref();
+    let savings = strpool::Global.savings();
+    for _ in 0..3 {
+        assert!(Keyword::While.str_ref().same_index(&first));
+    }
+    assert_eq!(strpool::Global.savings().0, savings.0);
+}
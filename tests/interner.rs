@@ -0,0 +1,52 @@
+use strpool::{Global, Interner, StrPool, StrRef};
+
+fn intern_words(interner: &mut impl Interner, words: &[&str]) -> Vec<StrRef> {
+    words.iter().map(|w| interner.intern(w)).collect()
+}
+
+#[test]
+fn generic_over_pools() {
+    let mut pool = StrPool::default();
+    let refs = intern_words(&mut pool, &["a", "b", "a"]);
+    assert!(refs[0].same_index(&refs[2]));
+    assert_eq!(Interner::resolve(&pool, refs[1].clone()), Some("b"));
+    assert_eq!(pool.len(), 2);
+
+    let refs = intern_words(&mut Global, &["x", "y"]);
+    assert_eq!(Global.resolve(refs[0].clone()), Some("x"));
+    assert_eq!(&*refs[1], "y");
+}
+
+// Only compiles while `Interner` is object safe.
+fn plugin(interner: &mut dyn Interner) -> StrRef {
+    strpool::intern_write!(interner, "plugin-{}", 7)
+}
+
+#[test]
+fn through_a_trait_object() {
+    let mut pool = StrPool::default();
+    let dyn_pool: &mut dyn Interner = &mut pool;
+    let r = plugin(dyn_pool);
+    assert!(dyn_pool.intern("plugin-7").same_index(&r));
+    let refs = intern_words(&mut &mut *dyn_pool, &["a", "plugin-7"]);
+    assert!(refs[1].same_index(&r));
+    assert_eq!(dyn_pool.resolve(r), Some("plugin-7"));
+    assert_eq!(pool.len(), 2);
+
+    let mut boxed: Box<dyn Interner> = Box::new(Global);
+    assert_eq!(&*plugin(&mut *boxed), "plugin-7");
+}
+
+#[test]
+fn intern_write_into_local_and_global_pools() {
+    let (a, b) = ("left", 2);
+    let mut pool = StrPool::default();
+    let local = strpool::intern_write!(pool, "{}-{}", a, b);
+    assert_eq!(pool.get(local.clone()), Some("left-2"));
+    assert!(strpool::intern_write!(pool, "{}-{}", "left", 2).same_index(&local));
+    assert_eq!(pool.len(), 1);
+
+    let global = strpool::intern_write!(Global, "{}-{}", a, b);
+    assert_eq!(&*global, "left-2");
+    assert!(StrRef::from("left-2").same_index(&global));
+}
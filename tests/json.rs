@@ -0,0 +1,36 @@
+#![cfg(feature = "json")]
+
+use serde_json::{json, Value};
+use strpool::InternedValue;
+
+fn strings(value: &InternedValue) -> Vec<&strpool::StrRef> {
+    match value {
+        InternedValue::String(r) => vec![r],
+        InternedValue::Array(items) => items.iter().flat_map(strings).collect(),
+        InternedValue::Object(entries) => {
+            entries.iter().flat_map(|(k, v)| std::iter::once(k).chain(strings(v))).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[test]
+fn duplicate_values_share_indices() {
+    let text = r#"{"primary": "red", "secondary": "red", "tags": ["red", "primary"], "n": 1}"#;
+    let value: InternedValue = serde_json::from_str(text).unwrap();
+    let refs = strings(&value);
+    let red: Vec<_> = refs.iter().filter(|r| ***r == *"red").copied().collect();
+    assert_eq!(red.len(), 3);
+    assert!(red.iter().all(|r| r.same_index(red[0])));
+    let primary: Vec<_> = refs.iter().filter(|r| ***r == *"primary").copied().collect();
+    assert!(primary[0].same_index(primary[1]));
+    assert_eq!(strpool::Global.len(), 5);
+}
+
+#[test]
+fn round_trips_through_value() {
+    let value = json!({"a": [null, true, 1.5, "x"], "b": {"c": "x"}});
+    let interned = InternedValue::from(&value);
+    assert_eq!(Value::from(&interned), value);
+    assert_eq!(serde_json::to_value(&interned).unwrap(), value);
+}
@@ -0,0 +1,27 @@
+use strpool::{NamespacedPool, TryPutError};
+
+#[test]
+fn pools_issue_refs_in_their_own_bands() {
+    let mut a = NamespacedPool::new(0, 1000);
+    let mut b = NamespacedPool::new(1000, 1000);
+    let ra = a.intern("shared").unwrap();
+    let rb = b.intern("shared").unwrap();
+    assert_eq!((ra.as_raw(), rb.as_raw()), (0, 1000));
+    assert!(a.contains(&ra) && !a.contains(&rb));
+    assert!(b.contains(&rb) && !b.contains(&ra));
+    assert_eq!(a.get(ra.clone()), Some("shared"));
+    assert_eq!(b.get(rb.clone()), Some("shared"));
+    assert_eq!(a.get(rb), None);
+    assert_eq!(b.get(ra), None);
+    assert!(b.intern("shared").unwrap().same_index(&b.intern("shared").unwrap()));
+}
+
+#[test]
+fn a_full_band_rejects_new_strings() {
+    let mut pool = NamespacedPool::new(10, 2);
+    assert_eq!(pool.range(), 10..12);
+    pool.intern("a").unwrap();
+    pool.intern("b").unwrap();
+    assert!(matches!(pool.intern("c"), Err(TryPutError::Full(_))));
+    assert_eq!(pool.intern("a").unwrap().as_raw(), 10);
+}
@@ -0,0 +1,20 @@
+use strpool::{Normalizer, StrPool};
+
+#[test]
+fn trim_then_lowercase_dedups_variants() {
+    let normalizer = Normalizer::new().trim().lowercase();
+    let mut pool = StrPool::default();
+    let a = pool.put_normalized("  Hello ", &normalizer);
+    let b = pool.put_normalized("HELLO", &normalizer);
+    let c = pool.put_normalized("hello", &normalizer);
+    assert!(a.same_index(&b) && b.same_index(&c));
+    assert_eq!(pool.get(a), Some("hello"));
+    assert_eq!(pool.len(), 1);
+}
+
+#[test]
+fn collapse_whitespace_borrows_normalized_input() {
+    let normalizer = Normalizer::new().collapse_whitespace();
+    assert_eq!(normalizer.apply("a  b\t c"), "a b c");
+    assert!(matches!(normalizer.apply("a b c"), std::borrow::Cow::Borrowed(_)));
+}
@@ -0,0 +1,24 @@
+use strpool::{codegen, PerfectPool};
+
+const KEYWORDS: &[&str] = &["fn", "let", "match", "if", "else", "while", "loop", "fn"];
+
+// Checked in as a build script would write it to `OUT_DIR`.
+static POOL: PerfectPool = include!("perfect/keywords.rs");
+
+#[test]
+fn generated_code_is_up_to_date() {
+    assert_eq!(codegen::generate(KEYWORDS), include_str!("perfect/keywords.rs"));
+}
+
+#[test]
+fn generated_pool_finds_members_only() {
+    assert_eq!(POOL.len(), 7);
+    for (i, keyword) in KEYWORDS[..7].iter().enumerate() {
+        let r = POOL.resolve_str(keyword).unwrap();
+        assert_eq!(r.as_raw(), i);
+        assert_eq!(POOL.get(r), Some(*keyword));
+    }
+    for other in ["", "f", "fnn", "Let", "for", "whilst"] {
+        assert_eq!(POOL.resolve_str(other), None);
+    }
+}
@@ -0,0 +1 @@
+::strpool::PerfectPool::from_tables(&["fn", "let", "match", "if", "else", "while", "loop", ], &[1, 6, ], &[4294967295, 4, 6, 4294967295, 3, 5, 1, 2, 0, ])
@@ -0,0 +1,509 @@
+use strpool::{EmptyPolicy, PoolFull, PoolOverflow, StrPool, StrRef, TryPutError};
+
+#[test]
+fn try_put_heap_stops_at_max_entries() {
+    let mut pool = StrPool::default();
+    pool.set_max_entries(Some(2));
+    assert_eq!(pool.max_entries(), Some(2));
+    let a = pool.try_put_heap("a".to_owned()).unwrap();
+    pool.try_put_heap("b".to_owned()).unwrap();
+    assert_eq!(pool.try_put_heap("c".to_owned()), Err(TryPutError::Full(PoolFull)));
+    // Existing strings are still handed out once the pool is full.
+    assert!(pool.try_put_heap("a".to_owned()).unwrap().same_index(&a));
+    assert_eq!(pool.len(), 2);
+    // `put_heap` ignores the cap.
+    pool.put_heap("c".to_owned());
+    assert_eq!(pool.len(), 3);
+}
+
+#[test]
+fn alias_shares_canonical_index() {
+    let mut pool = StrPool::default();
+    let color = pool.put_static("color");
+    pool.add_alias("colour", "color");
+    let colour = pool.put_heap("colour".to_owned());
+    assert!(colour.same_index(&color));
+    assert_eq!(pool.get(colour), Some("color"));
+    assert!(pool.get_or_intern("colour").same_index(&color));
+}
+
+#[test]
+fn alias_takes_precedence_over_existing_entry() {
+    let mut pool = StrPool::default();
+    let old = pool.put_static("grey");
+    let gray = pool.put_static("gray");
+    pool.add_alias("grey", "gray");
+    assert!(pool.put_static("grey").same_index(&gray));
+    // Refs issued before keep resolving to their own entry.
+    assert_eq!(pool.get(old), Some("grey"));
+}
+
+#[test]
+fn savings_grow_with_repeats() {
+    let mut pool = StrPool::default();
+    pool.put_heap("abcdef".to_owned());
+    assert_eq!(pool.savings(), (6, 6));
+    pool.put_heap("abcdef".to_owned());
+    pool.put_static("abcdef");
+    assert_eq!(pool.savings(), (18, 6));
+    pool.get_or_intern("xy");
+    assert_eq!(pool.savings(), (20, 8));
+}
+
+#[test]
+fn diff_compares_contents() {
+    let mut old = StrPool::default();
+    let mut new = StrPool::default();
+    for s in ["a", "b", "c"] {
+        old.put_static(s);
+    }
+    for s in ["d", "c", "b"] {
+        new.put_static(s);
+    }
+    let diff = old.diff(&new);
+    assert_eq!(diff.added, ["d"]);
+    assert_eq!(diff.removed, ["a"]);
+    assert_eq!(old.diff(&old), strpool::PoolDiff::default());
+}
+
+#[test]
+fn put_heap_or_borrow_prefers_base() {
+    use strpool::LayeredRef;
+
+    let mut base = StrPool::default();
+    base.put_static("shared");
+    let mut local = StrPool::default();
+    let hit = local.put_heap_or_borrow("shared".to_owned(), &base);
+    assert!(matches!(hit, LayeredRef::Base(_)));
+    assert_eq!(local.get_layered(hit, &base), Some("shared"));
+    assert!(local.is_empty());
+    let miss = local.put_heap_or_borrow("own".to_owned(), &base);
+    assert!(matches!(miss, LayeredRef::Local(_)));
+    assert_eq!(local.get_layered(miss, &base), Some("own"));
+    assert_eq!(local.len(), 1);
+}
+
+#[test]
+fn consuming_a_pool() {
+    let filled = || {
+        let mut pool = StrPool::default();
+        pool.put_static("a");
+        pool.put_heap("b".to_owned());
+        pool.put_static("c");
+        pool
+    };
+    let mut strings = Vec::new();
+    for s in filled() {
+        strings.push(s);
+    }
+    assert_eq!(strings, ["a", "b", "c"]);
+    assert_eq!(filled().into_iter().len(), 3);
+    let entries: Vec<(usize, String)> = filled().into_entries().collect();
+    assert_eq!(entries, [(0, "a".to_owned()), (1, "b".to_owned()), (2, "c".to_owned())]);
+}
+
+#[test]
+fn from_static_slice_keeps_keyword_indices() {
+    use strpool::StrRef;
+
+    const IF: StrRef = StrRef::from_raw(0);
+    const WHILE: StrRef = StrRef::from_raw(2);
+    let mut pool = StrPool::from_static_slice(&["if", "else", "while"]);
+    assert_eq!(pool.get(IF), Some("if"));
+    assert_eq!(pool.get(WHILE), Some("while"));
+    assert_eq!(pool.put_static("else").as_raw(), 1);
+    assert!(pool.is_static(StrRef::from_raw(1)).unwrap());
+}
+
+#[test]
+fn length_histograms() {
+    let mut pool = StrPool::default();
+    for s in ["", "a", "b", "abc", "abcd", "abcde", "abcdefghi"] {
+        pool.put_static(s);
+    }
+    let histogram: Vec<_> = pool.length_histogram().into_iter().collect();
+    assert_eq!(histogram, [(0, 1), (1, 2), (3, 1), (4, 1), (5, 1), (9, 1)]);
+    let buckets: Vec<_> = pool.length_histogram_pow2().into_iter().collect();
+    assert_eq!(buckets, [(0, 1), (1, 2), (4, 2), (8, 1), (16, 1)]);
+}
+
+#[test]
+fn compact_keeps_live_refs_and_remaps_them() {
+    let mut pool = StrPool::default();
+    let refs: Vec<_> = ["a", "b", "c", "d"].iter().map(|s| pool.put_static(s)).collect();
+    let live = [refs[1].clone(), refs[3].clone()];
+    let remap = pool.compact(&live);
+    assert_eq!(pool.len(), 2);
+    assert!(remap.get(&refs[0]).is_none());
+    assert!(remap.get(&refs[2]).is_none());
+    let b = remap.get(&refs[1]).unwrap();
+    let d = remap.get(&refs[3]).unwrap();
+    assert_eq!((b.as_raw(), d.as_raw()), (0, 1));
+    assert_eq!(pool.get(b), Some("b"));
+    assert_eq!(pool.get(d), Some("d"));
+    // Remapped entries are still found by content.
+    assert_eq!(pool.put_static("d").as_raw(), 1);
+}
+
+#[test]
+fn put_strict_rejects_unnormalized_input() {
+    use strpool::{NormalizationError, Normalizer};
+
+    let mut pool = StrPool::default();
+    pool.set_strict_policy(Normalizer::new().trim().lowercase());
+    let ok = pool.put_strict("clean".to_owned()).unwrap();
+    assert_eq!(pool.get(ok), Some("clean"));
+    let err = pool.put_strict(" Dirty".to_owned()).unwrap_err();
+    let (input, normalized) = (" Dirty".to_owned(), "dirty".to_owned());
+    assert_eq!(err, NormalizationError { input, normalized });
+    assert_eq!(pool.len(), 1);
+    // Without a policy everything is accepted.
+    let mut lax = StrPool::default();
+    assert!(lax.put_strict(" Dirty".to_owned()).is_ok());
+}
+
+#[test]
+fn on_insert_fires_once_per_distinct_string() {
+    use std::sync::{Arc, Mutex};
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let mut pool = StrPool::default();
+    let log = seen.clone();
+    pool.set_on_insert(Box::new(move |r, s| log.lock().unwrap().push((r, s.to_owned()))));
+    let a = pool.put_static("a");
+    pool.put_heap("a".to_owned());
+    let b = pool.put_heap("b".to_owned());
+    pool.put_static("b");
+    let seen = seen.lock().unwrap();
+    let seen: Vec<_> = seen.iter().map(|(r, s)| (r.as_raw(), s.as_str())).collect();
+    assert_eq!(seen, [(a.as_raw(), "a"), (b.as_raw(), "b")]);
+}
+
+#[test]
+fn entries_in_order_follow_insertion() {
+    let mut pool = StrPool::default();
+    for s in ["delta", "alpha", "charlie", "alpha", "bravo"] {
+        pool.put_heap(s.to_owned());
+    }
+    let entries: Vec<_> = pool.entries_in_order().collect();
+    assert_eq!(entries, [(0, "delta"), (1, "alpha"), (2, "charlie"), (3, "bravo")]);
+}
+
+#[test]
+fn ref_of_ptr_goes_by_address() {
+    let mut pool = StrPool::default();
+    let a = pool.put_heap("shared".to_owned()).as_raw();
+    let b = pool.push_heap("shared".to_owned()).as_raw();
+    let s = pool.get(StrRef::from_raw(b)).unwrap();
+    assert_eq!(pool.ref_of_ptr(s).map(|r| r.as_raw()), Some(b));
+    let first = pool.get(StrRef::from_raw(a)).unwrap();
+    assert_eq!(pool.ref_of_ptr(first).map(|r| r.as_raw()), Some(a));
+    assert!(pool.ref_of_ptr("shared").is_none());
+    assert!(pool.ref_of_ptr(&s[1..]).is_none());
+}
+
+#[test]
+fn ref_of_ptr_finds_deduplicated_entries_by_lookup() {
+    let mut pool = StrPool::default();
+    let refs: Vec<_> = ["a", "longer than eight", ""].map(|s| pool.put_heap(s.to_owned())).to_vec();
+    for r in &refs {
+        let s = pool.get(r.clone()).unwrap();
+        assert!(pool.ref_of_ptr(s).unwrap().same_index(r));
+    }
+    let copy = String::from("longer than eight");
+    assert!(pool.ref_of_ptr(&copy).is_none());
+}
+
+#[test]
+fn short_and_long_strings_dedup_across_the_8_byte_boundary() {
+    let mut pool = StrPool::default();
+    let words = ["", "a", "abcdefg", "abcdefgh", "abcdefghi", "abcdefgh\0", "\0", "é"];
+    let refs: Vec<_> = words.iter().map(|s| pool.put_heap(s.to_string()).as_raw()).collect();
+    assert_eq!(refs, (0..words.len()).collect::<Vec<_>>());
+    for (s, ptr) in words.iter().zip(refs) {
+        assert_eq!(pool.get_or_intern(s).as_raw(), ptr);
+        assert_eq!(pool.put_heap(s.to_string()).as_raw(), ptr);
+        assert_eq!(pool.get(StrRef::from_raw(ptr)), Some(*s));
+    }
+    assert_eq!(pool.len(), words.len());
+}
+
+#[test]
+fn push_heap_entries_stay_distinct() {
+    let mut pool = StrPool::default();
+    let old = pool.put_heap("gap".to_owned());
+    let a = pool.push_heap("line".to_owned());
+    let b = pool.push_heap("line".to_owned());
+    assert!(!a.same_index(&b));
+    assert_eq!(pool.get(a.clone()), pool.get(b.clone()));
+    assert_eq!(pool.len(), 3);
+    // Both survive a compaction as separate entries.
+    let remap = pool.compact([&a, &b]);
+    assert_eq!(remap.get(&old), None);
+    let (a, b) = (remap.get(&a).unwrap(), remap.get(&b).unwrap());
+    assert_eq!((a.as_raw(), b.as_raw()), (0, 1));
+    assert_eq!((pool.get(a), pool.get(b)), (Some("line"), Some("line")));
+}
+
+#[test]
+fn near_duplicates_pair_case_and_whitespace_variants() {
+    let mut pool = StrPool::default();
+    let foo = pool.put_static("Foo").as_raw();
+    pool.put_static("bar");
+    let variant = pool.put_static("foo ").as_raw();
+    let spaced = pool.put_static(" FOO").as_raw();
+    pool.put_static("fo o");
+    let pairs: Vec<_> =
+        pool.near_duplicates().iter().map(|(a, b)| (a.as_raw(), b.as_raw())).collect();
+    assert_eq!(pairs, [(foo, variant), (foo, spaced)]);
+}
+
+#[test]
+fn near_duplicates_collapse_interior_whitespace() {
+    let mut pool = StrPool::default();
+    let single = pool.put_static("a b").as_raw();
+    let double = pool.put_static("a  b").as_raw();
+    let tab = pool.put_static("A\t\n b").as_raw();
+    pool.put_static("ab");
+    let pairs: Vec<_> =
+        pool.near_duplicates().iter().map(|(a, b)| (a.as_raw(), b.as_raw())).collect();
+    assert_eq!(pairs, [(single, double), (single, tab)]);
+}
+
+#[test]
+fn unreferenced_lists_entries_missing_from_the_live_set() {
+    let mut pool = StrPool::default();
+    let a = pool.put_static("a");
+    pool.put_static("b");
+    let c = pool.push_heap("a".to_owned());
+    pool.push_heap("a".to_owned());
+    // Refs of a local pool, and equal `push_heap` entries told apart.
+    let unreferenced = |live: &[&StrRef]| {
+        let entries = pool.unreferenced(live.iter().copied());
+        entries.into_iter().map(|(r, s)| (r.as_raw(), s)).collect::<Vec<_>>()
+    };
+    assert_eq!(unreferenced(&[&a, &c]), [(1, "b"), (3, "a")]);
+    assert_eq!(unreferenced(&[]).len(), 4);
+}
+
+#[test]
+fn bulk_load_returns_refs_in_input_order() {
+    let mut pool = StrPool::default();
+    let existing = pool.put_static("b");
+    let strings: Vec<String> = ["d", "a", "b", "c", "a", "e"].map(String::from).to_vec();
+    let refs = pool.bulk_load(strings.clone());
+    assert_eq!(refs.len(), strings.len());
+    for (r, s) in refs.iter().zip(&strings) {
+        assert_eq!(pool.get(r.clone()), Some(s.as_str()));
+    }
+    assert!(refs[1].same_index(&refs[4]));
+    assert!(refs[2].same_index(&existing));
+    assert_eq!(pool.len(), 5);
+    // New entries are indexed by first appearance.
+    let order: Vec<_> = pool.entries_in_order().map(|(_, s)| s).collect();
+    assert_eq!(order, ["b", "d", "a", "c", "e"]);
+}
+
+#[test]
+fn rename_rekeys_the_entry_in_place() {
+    let mut pool = StrPool::default();
+    let r = pool.put_heap("before".to_owned());
+    let other = pool.put_static("other");
+    let renamed = pool.rename(r.clone(), "after".to_owned());
+    assert!(renamed.same_index(&r));
+    assert_eq!(pool.get(r.clone()), Some("after"));
+    assert!(pool.get_or_intern("after").same_index(&r));
+    // The old content is free to be interned anew.
+    let before = pool.get_or_intern("before");
+    assert!(!before.same_index(&r) && !before.same_index(&other));
+    assert_eq!(pool.len(), 3);
+}
+
+#[test]
+fn rename_to_existing_returns_its_ref() {
+    let mut pool = StrPool::default();
+    let r = pool.put_heap("before".to_owned());
+    let existing = pool.put_static("existing");
+    let renamed = pool.rename(r.clone(), "existing".to_owned());
+    assert!(renamed.same_index(&existing));
+    assert_eq!(pool.get(r), Some("before"));
+    assert_eq!(pool.len(), 2);
+}
+
+#[test]
+fn in_pool_formats_against_a_local_pool() {
+    let mut pool = StrPool::default();
+    pool.put_static("padding");
+    let r = pool.put_heap("local".to_owned());
+    let local = r.in_pool(&pool);
+    assert_eq!(format!("[{}] {:?}", local, local), "[local] \"local\"");
+    assert_eq!(local.len(), 5);
+    assert_eq!(&*local, "local");
+}
+
+#[test]
+fn empty_policy_share_uses_one_entry() {
+    let mut pool = StrPool::default();
+    assert_eq!(pool.empty_policy(), EmptyPolicy::Share);
+    let empty = pool.put_heap(String::new());
+    assert!(pool.get_or_intern("").same_index(&empty));
+    assert_eq!(pool.get(empty), Some(""));
+    assert_eq!(pool.len(), 1);
+}
+
+#[test]
+#[should_panic(expected = "EmptyPolicy::Error")]
+fn empty_policy_error_panics() {
+    let mut pool = StrPool::default();
+    pool.set_empty_policy(EmptyPolicy::Error);
+    pool.put_static("fine");
+    pool.put_heap(String::new());
+}
+
+#[test]
+fn empty_policy_error_fails_try_put() {
+    let mut pool = StrPool::default();
+    pool.set_empty_policy(EmptyPolicy::Error);
+    assert_eq!(pool.try_put_heap(String::new()), Err(TryPutError::Empty));
+    assert_eq!(pool.try_put_static(""), Err(TryPutError::Empty));
+    assert_eq!(pool.len(), 0);
+    assert!(pool.try_put_static("fine").is_ok());
+}
+
+#[test]
+fn empty_policy_none_maps_to_the_sentinel() {
+    let mut pool = StrPool::default();
+    pool.set_empty_policy(EmptyPolicy::None);
+    let none = pool.put_static("");
+    assert!(none.same_index(&StrRef::NONE));
+    assert_eq!(pool.get(none), None);
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn refs_for_finds_every_pushed_copy() {
+    let mut pool = StrPool::default();
+    let a = pool.push_heap("x".to_owned());
+    let other = pool.put_static("y");
+    let b = pool.push_heap("x".to_owned());
+    let refs = pool.refs_for("x");
+    assert_eq!(refs.len(), 2);
+    assert!(refs[0].same_index(&a) && refs[1].same_index(&b));
+    let refs = pool.refs_for("y");
+    assert_eq!(refs.len(), 1);
+    assert!(refs[0].same_index(&other));
+    assert!(pool.refs_for("z").is_empty());
+}
+
+#[test]
+fn changes_since_reports_adds_and_renames() {
+    let mut pool = StrPool::default();
+    let kept = pool.put_static("kept");
+    let texture = pool.put_heap("texture_v1".to_owned());
+    let snapshot = pool.snapshot();
+    pool.rename(texture.clone(), "texture_v2".to_owned());
+    let added = pool.put_heap("shader".to_owned());
+    pool.put_static("kept");
+    let changes = pool.changes_since(&snapshot);
+    assert_eq!(changes.added.len(), 1);
+    assert!(changes.added[0].same_index(&added));
+    assert_eq!(changes.modified.len(), 1);
+    assert!(changes.modified[0].0.same_index(&texture));
+    assert_eq!(changes.modified[0].1, "texture_v1");
+    assert!(changes.removed.is_empty());
+    assert!(pool.changes_since(&pool.snapshot()).added.is_empty());
+    assert_eq!(pool.get(kept), Some("kept"));
+}
+
+#[test]
+fn try_put_stops_at_the_index_limit() {
+    let mut pool = StrPool::default();
+    assert_eq!(pool.index_limit(), u32::MAX as usize + 1);
+    pool.set_index_limit(Some(2));
+    let a = pool.try_put_static("a").unwrap();
+    let b = pool.try_put_heap("b".to_owned()).unwrap();
+    assert_eq!(b.as_raw(), 1);
+    let overflow = Err(TryPutError::Overflow(PoolOverflow));
+    assert_eq!(pool.try_put_static("c"), overflow);
+    assert_eq!(pool.try_put_heap("c".to_owned()), overflow);
+    assert!(pool.try_put_static("a").unwrap().same_index(&a));
+    assert_eq!(pool.len(), 2);
+}
+
+#[test]
+fn put_boxed_keeps_the_allocation_on_a_miss() {
+    let mut pool = StrPool::default();
+    let boxed: Box<str> = "boxed content".into();
+    let addr = boxed.as_ptr();
+    let r = pool.put_boxed(boxed);
+    assert_eq!(pool.get(r.clone()).unwrap().as_ptr(), addr);
+    let again = pool.put_boxed("boxed content".into());
+    assert!(again.same_index(&r));
+    assert_eq!(pool.get(again).unwrap().as_ptr(), addr);
+    assert_eq!(pool.len(), 1);
+}
+
+#[test]
+fn bit_indices_fill_a_bitset_without_gaps() {
+    let mut pool = StrPool::default();
+    let refs: Vec<StrRef> = (0..100).map(|i| pool.put_heap(format!("flag{}", i))).collect();
+    pool.put_heap("flag7".to_owned());
+    assert_eq!(pool.max_index(), 100);
+    let mut bits = vec![0u64; pool.max_index().div_ceil(64)];
+    for r in &refs {
+        let i = r.bit_index();
+        assert_eq!(bits[i / 64] & 1 << (i % 64), 0);
+        bits[i / 64] |= 1 << (i % 64);
+    }
+    assert_eq!(bits.iter().map(|word| word.count_ones()).sum::<u32>(), 100);
+    assert_eq!(bits[1], (1 << 36) - 1);
+}
+
+#[test]
+fn put_canonical_keeps_the_first_display() {
+    let mut pool = StrPool::default();
+    let first = pool.put_canonical("new york", "New York".to_owned());
+    let second = pool.put_canonical("new york", "NEW YORK".to_owned());
+    assert!(second.same_index(&first));
+    assert_eq!(pool.get(second), Some("New York"));
+    let other = pool.put_canonical("boston", "Boston".to_owned());
+    assert!(!other.same_index(&first));
+    assert_eq!(pool.len(), 2);
+}
+
+#[test]
+fn entry_meta_matches_the_entries() {
+    let mut pool = StrPool::default();
+    pool.put_static("static");
+    pool.put_heap("heap".to_owned());
+    pool.put_boxed("boxed!".into());
+    let meta: Vec<_> = pool.entry_meta().map(|(r, kind, len)| (r.as_raw(), kind, len)).collect();
+    assert_eq!(meta, [(0, true, 6), (1, false, 4), (2, false, 6)]);
+    for (r, is_static, len) in pool.entry_meta() {
+        assert_eq!(pool.is_static(r.clone()), Some(is_static));
+        assert_eq!(pool.get(r).unwrap().len(), len);
+    }
+}
+
+#[test]
+fn save_and_load_keep_indices() {
+    let mut pool = StrPool::default();
+    let refs = [
+        pool.put_static("static"),
+        pool.put_heap("heap".to_owned()),
+        pool.push_heap("heap".to_owned()),
+        pool.put_heap(String::new()),
+    ];
+    let mut bytes = Vec::new();
+    pool.save(&mut bytes).unwrap();
+    let mut loaded = StrPool::load(bytes.as_slice()).unwrap();
+    assert_eq!(loaded.len(), 4);
+    for r in &refs {
+        assert_eq!(loaded.get(r.clone()), pool.get(r.clone()));
+    }
+    // Pushed entries stay out of the dedup set.
+    assert_eq!(loaded.refs_for("heap").len(), 2);
+    assert!(loaded.get_or_intern("heap").same_index(&refs[1]));
+    bytes[0] = b'X';
+    assert!(StrPool::load(bytes.as_slice()).is_err());
+}
@@ -0,0 +1,23 @@
+#![cfg(feature = "profile")]
+
+use strpool::StrPool;
+
+#[test]
+fn hot_entries_rank_by_resolutions() {
+    let mut pool = StrPool::default();
+    let cold = pool.put_static("cold");
+    let warm = pool.put_static("warm");
+    let hot = pool.put_static("hot");
+    for _ in 0..5 {
+        pool.get(hot.clone());
+    }
+    for _ in 0..2 {
+        pool.get(warm.clone());
+    }
+    let ranked: Vec<_> = pool.hot_entries(2).into_iter().map(|(_, s, n)| (s, n)).collect();
+    assert_eq!(ranked, [("hot", 5), ("warm", 2)]);
+    let all = pool.hot_entries(10);
+    assert_eq!(all.len(), 3);
+    assert!(all[2].0.same_index(&cold));
+    assert_eq!(all[2].2, 0);
+}
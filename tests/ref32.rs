@@ -0,0 +1,21 @@
+use strpool::{PoolOverflow, StrRef, StrRef32};
+
+#[test]
+fn round_trips_through_the_compact_form() {
+    let r = StrRef::from("name");
+    let compact = StrRef32::try_from(r.clone()).unwrap();
+    assert_eq!(std::mem::size_of::<StrRef32>(), 4);
+    assert_eq!(compact.as_raw() as usize, r.as_raw());
+    assert_eq!(&*compact, "name");
+    assert!(StrRef::from(compact).same_index(&r));
+    assert_eq!(strpool::intern32("name"), Ok(compact));
+}
+
+#[test]
+fn indices_past_u32_fail_to_convert() {
+    let last = StrRef::from_raw(u32::MAX as usize);
+    assert_eq!(StrRef32::try_from(last).map(|r| r.as_raw()), Ok(u32::MAX));
+    if let Ok(past) = usize::try_from(u32::MAX as u64 + 1) {
+        assert_eq!(StrRef32::try_from(StrRef::from_raw(past)), Err(PoolOverflow));
+    }
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "serde")]
+
+use strpool::{serde::SortedDict, StrRef};
+
+fn sorted_dict_json(order: &'static [&'static str]) -> String {
+    std::thread::spawn(move || {
+        for s in order {
+            strpool::put_static(s);
+        }
+        let refs: Vec<StrRef> = ["b", "a", "c", "a"].into_iter().map(StrRef::from).collect();
+        serde_json::to_string(&SortedDict(refs)).unwrap()
+    })
+    .join()
+    .unwrap()
+}
+
+#[test]
+fn sorted_dict_ignores_interning_order() {
+    let a = sorted_dict_json(&["a", "b", "c"]);
+    let b = sorted_dict_json(&["c", "b", "a"]);
+    assert_eq!(a, b);
+    assert_eq!(a, r#"[["a","b","c"],[1,0,2,0]]"#);
+}
+
+#[test]
+fn sorted_dict_round_trip() {
+    let refs: Vec<StrRef> = ["x", "y", "x"].into_iter().map(StrRef::from).collect();
+    let json = serde_json::to_string(&SortedDict(&refs[..])).unwrap();
+    let SortedDict(back): SortedDict<Vec<StrRef>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, refs);
+    assert!(serde_json::from_str::<SortedDict<Vec<StrRef>>>(r#"[["x"],[1]]"#).is_err());
+}
+
+#[test]
+fn duplicate_keys_share_an_index() {
+    use std::collections::HashMap;
+
+    let json = r#"[{"id": 1, "name": 2}, {"id": 3, "name": 4}, {"id": 5}]"#;
+    let rows: Vec<HashMap<StrRef, u32>> = serde_json::from_str(json).unwrap();
+    let id = StrRef::from("id");
+    for row in &rows {
+        let (key, _) = row.get_key_value("id").unwrap();
+        assert!(key.same_index(&id));
+    }
+    assert_eq!(rows[1]["name"], 4);
+}
+
+#[test]
+fn deserialize_borrowed_needs_a_borrowed_string() {
+    let mut de = serde_json::Deserializer::from_str(r#""plain""#);
+    let r = strpool::serde::deserialize_borrowed(&mut de).unwrap();
+    assert_eq!(&*r, "plain");
+    // Escapes force serde_json to unescape into a buffer of its own.
+    let mut de = serde_json::Deserializer::from_str(r#""esc\"aped""#);
+    assert!(strpool::serde::deserialize_borrowed(&mut de).is_err());
+}
+
+#[test]
+fn seed_deserializes_into_the_given_pool() {
+    use serde::de::DeserializeSeed;
+    use strpool::{serde::StrRefSeed, StrPool};
+
+    let mut pool = StrPool::default();
+    let mut de = serde_json::Deserializer::from_str(r#""local""#);
+    let r = StrRefSeed(&mut pool).deserialize(&mut de).unwrap();
+    assert_eq!(pool.get(r), Some("local"));
+    assert_eq!(pool.len(), 1);
+    assert_eq!(strpool::Global.len(), 0);
+}
+
+#[test]
+fn deserializes_from_utf8_bytes() {
+    use serde::{de::value, Deserialize};
+
+    let de = value::BytesDeserializer::<value::Error>::new("bytes".as_bytes());
+    let r = StrRef::deserialize(de).unwrap();
+    assert!(r.same_index(&StrRef::from("bytes")));
+    let de = value::BorrowedBytesDeserializer::<value::Error>::new(b"borrowed");
+    assert_eq!(&*StrRef::deserialize(de).unwrap(), "borrowed");
+    let de = value::BytesDeserializer::<value::Error>::new(b"\xff\xfe");
+    assert!(StrRef::deserialize(de).is_err());
+}
@@ -0,0 +1,25 @@
+use strpool::{SmallStrRef, StrRef};
+
+#[test]
+fn short_strings_stay_inline() {
+    let short = SmallStrRef::new("abc");
+    assert!(short.is_inline());
+    assert_eq!(&*short, "abc");
+    assert!(SmallStrRef::new("1234567").is_inline());
+}
+
+#[test]
+fn long_strings_are_pooled() {
+    let long = SmallStrRef::new("12345678");
+    assert!(!long.is_inline());
+    assert_eq!(&*long, "12345678");
+}
+
+#[test]
+fn equality_across_representations() {
+    let inline = SmallStrRef::new("tok");
+    let pooled = SmallStrRef::from(StrRef::from("tok"));
+    assert!(!pooled.is_inline());
+    assert_eq!(inline, pooled);
+    assert_ne!(inline, SmallStrRef::new("tik"));
+}
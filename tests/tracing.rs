@@ -0,0 +1,51 @@
+#![cfg(feature = "tracing")]
+
+use std::sync::{Arc, Mutex};
+
+use strpool::StrPool;
+use tracing::{span, Event, Level, Metadata, Subscriber};
+
+// Records the level of every event.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<Level>>>);
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        self.0.lock().unwrap().push(*event.metadata().level());
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+#[test]
+fn new_insertions_emit_events_and_hits_do_not() {
+    let recorder = Recorder::default();
+    let events = recorder.0.clone();
+    tracing::subscriber::with_default(recorder, || {
+        let mut pool = StrPool::default();
+        pool.put_static("first");
+        // The first entry doubles the pool.
+        assert_eq!(*events.lock().unwrap(), [Level::TRACE, Level::DEBUG]);
+        pool.put_heap("second".to_owned());
+        assert_eq!(events.lock().unwrap().len(), 4);
+        pool.put_heap("third".to_owned());
+        assert_eq!(events.lock().unwrap().len(), 5);
+        pool.put_static("third");
+        pool.put_heap("first".to_owned());
+        assert_eq!(events.lock().unwrap().len(), 5);
+    });
+}
@@ -0,0 +1,14 @@
+use strpool::TypedRef;
+
+struct User;
+struct Tag;
+
+#[test]
+fn domains_share_the_pool() {
+    let user: TypedRef<User> = strpool::intern("alice");
+    let tag: TypedRef<Tag> = strpool::intern("alice");
+    assert!(user.as_str_ref().same_index(tag.as_str_ref()));
+    assert_eq!(user, strpool::intern::<User>("alice"));
+    assert_ne!(user, strpool::intern::<User>("bob"));
+    assert_eq!(&*tag, "alice");
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "unicode")]
+
+use strpool::StrRef;
+
+#[test]
+fn truncate_graphemes_ascii() {
+    let r = StrRef::from("abcdef");
+    assert_eq!(&*r.truncate_graphemes(3, "…"), "abc…");
+}
+
+#[test]
+fn truncate_graphemes_keeps_clusters_whole() {
+    // Two clusters of two chars each, then a plain one.
+    let r = StrRef::from("e\u{301}a\u{308}b");
+    assert_eq!(&*r.truncate_graphemes(2, "..."), "e\u{301}a\u{308}...");
+    assert_eq!(&*r.truncate_graphemes(1, ""), "e\u{301}");
+}
+
+#[test]
+fn truncate_graphemes_short_circuits() {
+    let r = StrRef::from("e\u{301}a");
+    assert!(r.truncate_graphemes(2, "…").same_index(&r));
+    assert!(r.truncate_graphemes(5, "…").same_index(&r));
+}
+
+#[test]
+fn substr_graphemes_counts_clusters_not_bytes() {
+    // Clusters: "e\u{301}", "a\u{308}", "b", each a different byte length.
+    let r = StrRef::from("e\u{301}a\u{308}b");
+    assert_eq!(&*r.substr_graphemes(1..2).unwrap(), "a\u{308}");
+    assert_eq!(&*r.substr_graphemes(1..3).unwrap(), "a\u{308}b");
+    assert!(r.substr_graphemes(0..3).unwrap().same_index(&r));
+    assert_eq!(&*r.substr_graphemes(3..3).unwrap(), "");
+    assert_eq!(r.substr_graphemes(2..4), None);
+    assert_eq!(r.substr_graphemes(4..4), None);
+    #[allow(clippy::reversed_empty_ranges)]
+    let decreasing = r.substr_graphemes(2..1);
+    assert_eq!(decreasing, None);
+}
@@ -0,0 +1,17 @@
+// Single-threaded wasm32 keeps the global pool in a static, e.g. run with
+// `cargo test --target wasm32-wasip1` and a wasm runtime as the runner.
+#![cfg(all(target_arch = "wasm32", not(target_feature = "atomics")))]
+
+use strpool::{Interner, StrRef};
+
+#[test]
+fn static_global_pool_interns_and_resolves() {
+    let a = StrRef::from("wasm");
+    let b = strpool::put_heap("wasm".to_owned());
+    assert!(a.same_index(&b));
+    assert_eq!(&*b, "wasm");
+    assert_eq!(strpool::try_global(|pool| pool.resolve(a.clone()).map(str::len)), Some(Some(4)));
+    let remap = strpool::reset_heap_only();
+    assert_eq!(remap.get(&a), None);
+    assert!(!a.is_resolvable());
+}
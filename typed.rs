@@ -0,0 +1,88 @@
+use std::{marker::PhantomData, ops::Deref};
+
+use crate::StrRef;
+
+// Shares the global pool with plain refs, but refs of different domains `T`
+// cannot be compared or mixed up.
+pub struct TypedRef<T>(StrRef, PhantomData<fn() -> T>);
+
+pub fn intern<T>(str: &str) -> TypedRef<T> {
+    TypedRef::new(crate::get_or_intern(str))
+}
+
+impl<T> TypedRef<T> {
+    pub fn new(r: StrRef) -> Self {
+        TypedRef(r, PhantomData)
+    }
+
+    pub fn as_str_ref(&self) -> &StrRef {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> StrRef {
+        self.0
+    }
+}
+
+impl<T> Clone for TypedRef<T> {
+    fn clone(&self) -> Self {
+        TypedRef::new(self.0.clone())
+    }
+}
+
+impl<T> Deref for TypedRef<T> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> PartialEq<Self> for TypedRef<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T> Eq for TypedRef<T> {}
+
+impl<T> PartialOrd<Self> for TypedRef<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for TypedRef<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl<T> core::hash::Hash for TypedRef<T> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl<T> core::fmt::Debug for TypedRef<T> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+
+impl<T> core::fmt::Display for TypedRef<T> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+
+// Refs of different domains do not even compile when compared.
+#[cfg(doctest)]
+/// ```compile_fail,E0308
+/// struct User;
+/// struct Tag;
+/// let user = strpool::intern::<User>("x");
+/// let tag = strpool::intern::<Tag>("x");
+/// assert!(user == tag);
+/// ```
+struct MixedDomains;
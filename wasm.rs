@@ -0,0 +1,34 @@
+use std::cell::{Cell, UnsafeCell};
+
+use crate::StrPool;
+
+// wasm32 without the atomics feature only ever runs one thread, so the global
+// pool is a plain static instead of a thread local there. The flag backing
+// `run_hook` lives next to it.
+struct SingleThreaded {
+    pool: UnsafeCell<Option<StrPool>>,
+    in_hook: Cell<bool>,
+}
+
+unsafe impl Sync for SingleThreaded {}
+
+static GLOBAL: SingleThreaded =
+    SingleThreaded { pool: UnsafeCell::new(None), in_hook: Cell::new(false) };
+
+pub(crate) unsafe fn global<'a>() -> &'a mut StrPool {
+    assert!(!GLOBAL.in_hook.get(), "global pool used from an insert hook");
+    (*GLOBAL.pool.get()).get_or_insert_with(StrPool::default)
+}
+
+pub(crate) fn run_hook<R>(f: impl FnOnce() -> R) -> R {
+    struct Restore(bool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            GLOBAL.in_hook.set(self.0);
+        }
+    }
+
+    let _restore = Restore(GLOBAL.in_hook.replace(true));
+    f()
+}